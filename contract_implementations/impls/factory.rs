@@ -0,0 +1,128 @@
+use crate::traits::errors::FactoryError;
+use crate::traits::staking::PARAMETER_MANAGER;
+use crate::traits::staking::PAUSER;
+use crate::traits::upgradeable::*;
+use ink::prelude::vec::Vec;
+use openbrush::contracts::access_control::*;
+use openbrush::storage::Mapping;
+use openbrush::traits::AccountId;
+use openbrush::traits::Balance;
+use openbrush::traits::DefaultEnv;
+use openbrush::traits::Hash;
+use openbrush::traits::Storage;
+
+#[derive(Debug, Default)]
+#[openbrush::storage_item]
+pub struct FactoryData {
+    #[lazy]
+    pub code_hash: Hash,
+    /// Pool deployed for each staking token.
+    pub pools: Mapping<AccountId, AccountId>,
+    #[lazy]
+    pub pool_list: Vec<AccountId>,
+    pub pool_code_hash: Mapping<AccountId, Hash>,
+}
+
+pub trait FactoryImpl: Storage<FactoryData> {
+    /// Deploys the pool and hands it over to the caller as its admin. The factory keeps only
+    /// the pool's `UPGRADER` role, which `upgrade_pools` needs; the new admin may revoke it
+    /// to opt out of factory upgrades.
+    fn create_pool_impl(
+        &mut self,
+        token: AccountId,
+        reward_token: AccountId,
+        reward_rate: Balance,
+    ) -> Result<AccountId, FactoryError> {
+        if self.data().pools.get(&token).is_some() {
+            return Err(FactoryError::PoolExists);
+        }
+        let code_hash = self.data().code_hash.get().ok_or(FactoryError::CodeHashNotSet)?;
+
+        let mut pool_list = self.pools_impl();
+        // the index keeps addresses unique when a token is deployed with the same parameters twice
+        let salt = (pool_list.len() as u32).to_le_bytes();
+        let pool = self._instantiate_pool(code_hash, token, reward_token, reward_rate, &salt)?;
+
+        let caller = Self::env().caller();
+        let factory = Self::env().account_id();
+        for role in [DEFAULT_ADMIN_ROLE, PARAMETER_MANAGER, PAUSER] {
+            AccessControlRef::grant_role(&pool, role, Some(caller))?;
+        }
+        // the admin role goes last, as renouncing it first would forbid the others
+        for role in [PARAMETER_MANAGER, PAUSER, DEFAULT_ADMIN_ROLE] {
+            AccessControlRef::renounce_role(&pool, role, Some(factory))?;
+        }
+
+        self.data().pools.insert(&token, &pool);
+        pool_list.push(pool);
+        self.data().pool_list.set(&pool_list);
+        self.data().pool_code_hash.insert(&pool, &code_hash);
+        self._emit_pool_deployed_event(token, pool, code_hash);
+
+        Ok(pool)
+    }
+
+    /// Instantiates a staking contract from `code_hash`; implemented by the contract, which
+    /// knows the staking contract's constructor.
+    fn _instantiate_pool(
+        &mut self,
+        code_hash: Hash,
+        token: AccountId,
+        reward_token: AccountId,
+        reward_rate: Balance,
+        salt: &[u8],
+    ) -> Result<AccountId, FactoryError>;
+
+    fn set_code_hash_impl(&mut self, code_hash: Hash) -> Result<(), FactoryError> {
+        self.data().code_hash.set(&code_hash);
+        self._emit_code_hash_changed_event(code_hash);
+        Ok(())
+    }
+
+    /// A pool refusing the upgrade, e.g. because its admin revoked the factory's `UPGRADER`
+    /// role, is skipped so it cannot hold back the rest of the batch.
+    fn upgrade_pools_impl(&mut self, start: u32, limit: u32) -> Result<u32, FactoryError> {
+        let code_hash = self.data().code_hash.get().ok_or(FactoryError::CodeHashNotSet)?;
+
+        let mut upgraded = 0;
+        for pool in self.pools_impl().into_iter().skip(start as usize).take(limit as usize) {
+            if self.data().pool_code_hash.get(&pool) == Some(code_hash) {
+                continue;
+            }
+
+            if UpgradeableRef::set_code_hash(&pool, code_hash).is_err() {
+                self._emit_pool_upgrade_skipped_event(pool, code_hash);
+                continue;
+            }
+            self.data().pool_code_hash.insert(&pool, &code_hash);
+            self._emit_pool_upgraded_event(pool, code_hash);
+            upgraded += 1;
+        }
+
+        Ok(upgraded)
+    }
+
+    fn pool_of_impl(&self, token: AccountId) -> Option<AccountId> {
+        self.data().pools.get(&token)
+    }
+
+    fn pools_impl(&self) -> Vec<AccountId> {
+        self.data().pool_list.get().unwrap_or_default()
+    }
+
+    fn code_hash_impl(&self) -> Option<Hash> {
+        self.data().code_hash.get()
+    }
+
+    fn pool_code_hash_impl(&self, pool: AccountId) -> Option<Hash> {
+        self.data().pool_code_hash.get(&pool)
+    }
+
+    fn _emit_pool_deployed_event(&self, _token: AccountId, _pool: AccountId, _code_hash: Hash) {}
+
+    fn _emit_code_hash_changed_event(&self, _code_hash: Hash) {}
+
+    fn _emit_pool_upgraded_event(&self, _pool: AccountId, _code_hash: Hash) {}
+
+    fn _emit_pool_upgrade_skipped_event(&self, _pool: AccountId, _code_hash: Hash) {}
+}
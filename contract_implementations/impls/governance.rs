@@ -0,0 +1,232 @@
+use crate::impls::staking::BoostTier;
+use crate::impls::staking::PoolId;
+use crate::math;
+use crate::traits::errors::GovernanceError;
+use crate::traits::errors::MathError;
+use crate::traits::staking::*;
+use ink::prelude::vec::Vec;
+use openbrush::storage::Mapping;
+use openbrush::traits::AccountId;
+use openbrush::traits::Balance;
+use openbrush::traits::DefaultEnv;
+use openbrush::traits::Storage;
+use openbrush::traits::Timestamp;
+
+pub type ProposalId = u32;
+
+#[derive(Debug, Default)]
+#[openbrush::storage_item]
+pub struct GovernanceData {
+    /// Staking contract whose delegated stake is the voting weight and whose parameters
+    /// proposals change.
+    #[lazy]
+    pub staking: AccountId,
+    /// How long proposals accept votes, in milliseconds of block time.
+    #[lazy]
+    pub voting_period: Timestamp,
+    /// Votes in favour a proposal needs to pass.
+    #[lazy]
+    pub quorum: Balance,
+    /// Voting weight needed to create a proposal.
+    #[lazy]
+    pub proposal_threshold: Balance,
+    pub proposals: Mapping<ProposalId, Proposal>,
+    #[lazy]
+    pub proposal_count: ProposalId,
+    /// Whether each account voted for or against each proposal.
+    pub votes: Mapping<(ProposalId, AccountId), bool>,
+}
+
+/// Change to the staking contract a proposal makes once it passes.
+#[derive(scale::Decode, scale::Encode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum ProposalAction {
+    RewardRate(PoolId, Balance),
+    BoostSchedule(Vec<BoostTier>),
+    Pause,
+    Unpause,
+}
+
+#[derive(scale::Decode, scale::Encode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Proposal {
+    pub proposer: AccountId,
+    pub action: ProposalAction,
+    /// Voting weight is read from the staking contract as of this time.
+    pub snapshot: Timestamp,
+    /// Last moment votes are accepted.
+    pub vote_end: Timestamp,
+    pub votes_for: Balance,
+    pub votes_against: Balance,
+    pub executed: bool,
+}
+
+#[derive(scale::Decode, scale::Encode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ProposalState {
+    Active,
+    Defeated,
+    Succeeded,
+    Executed,
+}
+
+/// Stake-weighted governance of a staking contract. Voting weight is the voter's
+/// `delegated_power_at` the proposal's snapshot, so stake moved to another account after a
+/// proposal is created cannot vote on it twice. The snapshot is taken on the staking
+/// contract's `current_timestamp`, the clock its stake history is recorded by, while voting
+/// runs on block time. Where that clock is set by the staking admin rather than the chain,
+/// the admin decides which stake a snapshot sees and has to be trusted accordingly; the clock
+/// only moves forward, so history already recorded cannot be rewritten. Vote-escrow power is
+/// not counted: locks keep no history, so it could not be read as of the snapshot and a lock
+/// made after a proposal could sway it.
+/// The governance contract needs the staking contract's `PARAMETER_MANAGER` and `PAUSER`
+/// roles to execute proposals.
+pub trait GovernanceImpl: Storage<GovernanceData> {
+    fn propose_impl(&mut self, action: ProposalAction) -> Result<ProposalId, GovernanceError> {
+        let staking = self._staking()?;
+        let proposer = Self::env().caller();
+        let now = Self::env().block_timestamp();
+        // stake changed at the moment of the proposal does not count
+        let snapshot = StakingRef::current_timestamp(&staking).saturating_sub(1);
+
+        let weight = StakingRef::delegated_power_at(&staking, proposer, snapshot);
+        if weight < self.proposal_threshold_impl() {
+            return Err(GovernanceError::BelowProposalThreshold);
+        }
+
+        let proposal_id = self.proposal_count_impl();
+        let proposal_count = proposal_id.checked_add(1).ok_or(MathError::Overflow)?;
+        let vote_end = math::add_duration(now, self.voting_period_impl())?;
+
+        let proposal = Proposal {
+            proposer,
+            action,
+            snapshot,
+            vote_end,
+            votes_for: 0,
+            votes_against: 0,
+            executed: false,
+        };
+        self.data().proposals.insert(&proposal_id, &proposal);
+        self.data().proposal_count.set(&proposal_count);
+        self._emit_proposal_created_event(proposal_id, proposer, proposal.action, vote_end);
+
+        Ok(proposal_id)
+    }
+
+    /// Casts the caller's whole voting weight at the proposal's snapshot. Each account
+    /// votes once per proposal.
+    fn vote_impl(&mut self, proposal_id: ProposalId, support: bool) -> Result<(), GovernanceError> {
+        let staking = self._staking()?;
+        let voter = Self::env().caller();
+        let mut proposal = self._proposal(proposal_id)?;
+        if Self::env().block_timestamp() > proposal.vote_end {
+            return Err(GovernanceError::VotingClosed);
+        }
+        if self.has_voted_impl(proposal_id, voter) {
+            return Err(GovernanceError::AlreadyVoted);
+        }
+
+        let weight = StakingRef::delegated_power_at(&staking, voter, proposal.snapshot);
+        if weight == 0 {
+            return Err(GovernanceError::NoVotingPower);
+        }
+
+        if support {
+            proposal.votes_for = math::add(proposal.votes_for, weight)?;
+        } else {
+            proposal.votes_against = math::add(proposal.votes_against, weight)?;
+        }
+        self.data().proposals.insert(&proposal_id, &proposal);
+        self.data().votes.insert(&(proposal_id, voter), &support);
+        self._emit_vote_cast_event(proposal_id, voter, support, weight);
+
+        Ok(())
+    }
+
+    /// Applies a succeeded proposal to the staking contract. Anyone may execute it.
+    fn execute_impl(&mut self, proposal_id: ProposalId) -> Result<(), GovernanceError> {
+        let staking = self._staking()?;
+        let mut proposal = self._proposal(proposal_id)?;
+        if self.state_impl(proposal_id) != Some(ProposalState::Succeeded) {
+            return Err(GovernanceError::NotSucceeded);
+        }
+
+        proposal.executed = true;
+        self.data().proposals.insert(&proposal_id, &proposal);
+
+        match proposal.action {
+            ProposalAction::RewardRate(pool_id, reward_rate) => StakingAdminRef::set_reward_rate(&staking, pool_id, reward_rate)?,
+            ProposalAction::BoostSchedule(schedule) => StakingAdminRef::set_boost_schedule(&staking, schedule)?,
+            ProposalAction::Pause => StakingAdminRef::pause(&staking)?,
+            ProposalAction::Unpause => StakingAdminRef::unpause(&staking)?,
+        }
+        self._emit_proposal_executed_event(proposal_id);
+
+        Ok(())
+    }
+
+    /// A proposal passes once voting has ended with more votes for than against and at
+    /// least `quorum` votes for.
+    fn state_impl(&self, proposal_id: ProposalId) -> Option<ProposalState> {
+        let proposal = self.data().proposals.get(&proposal_id)?;
+        let state = if proposal.executed {
+            ProposalState::Executed
+        } else if Self::env().block_timestamp() <= proposal.vote_end {
+            ProposalState::Active
+        } else if proposal.votes_for > proposal.votes_against && proposal.votes_for >= self.quorum_impl() {
+            ProposalState::Succeeded
+        } else {
+            ProposalState::Defeated
+        };
+        Some(state)
+    }
+
+    fn proposal_impl(&self, proposal_id: ProposalId) -> Option<Proposal> {
+        self.data().proposals.get(&proposal_id)
+    }
+
+    fn proposal_count_impl(&self) -> ProposalId {
+        self.data().proposal_count.get().unwrap_or_default()
+    }
+
+    fn has_voted_impl(&self, proposal_id: ProposalId, account: AccountId) -> bool {
+        self.data().votes.get(&(proposal_id, account)).is_some()
+    }
+
+    fn staking_impl(&self) -> Option<AccountId> {
+        self.data().staking.get()
+    }
+
+    fn voting_period_impl(&self) -> Timestamp {
+        self.data().voting_period.get().unwrap_or_default()
+    }
+
+    fn quorum_impl(&self) -> Balance {
+        self.data().quorum.get().unwrap_or_default()
+    }
+
+    fn proposal_threshold_impl(&self) -> Balance {
+        self.data().proposal_threshold.get().unwrap_or_default()
+    }
+
+    fn _staking(&self) -> Result<AccountId, GovernanceError> {
+        self.staking_impl().ok_or(GovernanceError::StakingNotSet)
+    }
+
+    fn _proposal(&self, proposal_id: ProposalId) -> Result<Proposal, GovernanceError> {
+        self.proposal_impl(proposal_id).ok_or(GovernanceError::ProposalNotFound)
+    }
+
+    fn _emit_proposal_created_event(&self, _proposal_id: ProposalId, _proposer: AccountId, _action: ProposalAction, _vote_end: Timestamp) {}
+
+    fn _emit_vote_cast_event(&self, _proposal_id: ProposalId, _voter: AccountId, _support: bool, _weight: Balance) {}
+
+    fn _emit_proposal_executed_event(&self, _proposal_id: ProposalId) {}
+}
@@ -1,2 +1,8 @@
+#[cfg(feature = "factory")]
+pub mod factory;
+#[cfg(feature = "governance")]
+pub mod governance;
 #[cfg(feature = "staking")]
 pub mod staking;
+#[cfg(feature = "staking")]
+pub mod vote_escrow;
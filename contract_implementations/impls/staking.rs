@@ -1,108 +1,2022 @@
+use crate::math;
+use crate::math::Rounding;
+use crate::traits::errors::MathError;
 use crate::traits::errors::StakingError;
 use ink::prelude::vec::Vec;
+use openbrush::contracts::psp22::extensions::burnable::*;
 use openbrush::contracts::psp22::extensions::mintable::*;
 use openbrush::storage::Mapping;
 use openbrush::traits::AccountId;
 use openbrush::traits::Balance;
-use openbrush::storage::Storage;
+use openbrush::traits::DefaultEnv;
+use openbrush::traits::Storage;
 use openbrush::traits::Timestamp;
 
 #[derive(Debug, Default)]
 #[openbrush::storage_item]
 pub struct StakingData {
-    pub stakes: Mapping<AccountId, StakeInfo>,
+    pub pools: Mapping<PoolId, Pool>,
     #[lazy]
+    pub pool_count: PoolId,
+    #[lazy]
+    pub total_allocation_weight: u32,
+    /// Layout version the stored data was last migrated to, see `STORAGE_VERSION`.
+    #[lazy]
+    pub storage_version: u32,
+    /// Stakes of the first release, one per account and without pools. Nothing writes here
+    /// any more; `migrate` moves them into `stake_records`.
+    pub stakes: Mapping<AccountId, LegacyStakeInfo>,
+    /// Token the first release staked and paid rewards in.
+    #[lazy]
+    pub token: AccountId,
+    pub stake_records: Mapping<(PoolId, AccountId, PositionId), StakeRecord>,
+    /// Ids of every open position of an account in a pool.
+    pub positions: Mapping<(PoolId, AccountId), Vec<PositionId>>,
+    pub last_position_id: Mapping<(PoolId, AccountId), PositionId>,
+    /// Sum of all positions of an account in a pool, which extra rewards accrue on.
+    pub account_staked: Mapping<(PoolId, AccountId), Balance>,
+    /// History of `account_staked` in time order, keyed by (pool, account, index).
+    pub stake_checkpoints: Mapping<(PoolId, AccountId, u32), Checkpoint>,
+    pub stake_checkpoint_count: Mapping<(PoolId, AccountId), u32>,
+    /// History of each pool's `total_staked` in time order, keyed by (pool, index).
+    pub total_staked_checkpoints: Mapping<(PoolId, u32), Checkpoint>,
+    pub total_staked_checkpoint_count: Mapping<PoolId, u32>,
+    /// History of each pool's `reward_rate` in time order, keyed by (pool, index). Empty
+    /// until the rate first changes.
+    pub rate_checkpoints: Mapping<(PoolId, u32), RateCheckpoint>,
+    pub rate_checkpoint_count: Mapping<PoolId, u32>,
+    /// Whom each account's governance weight, and optionally its rewards, are delegated to.
+    pub delegations: Mapping<AccountId, Delegation>,
+    /// `DEFAULT_POOL` stake other accounts delegated to each account.
+    pub received_delegations: Mapping<AccountId, Balance>,
+    /// History of `delegated_power` in time order, keyed by (account, index).
+    pub power_checkpoints: Mapping<(AccountId, u32), Checkpoint>,
+    pub power_checkpoint_count: Mapping<AccountId, u32>,
+    /// How each account's rewards in a pool are handled; absent means `RewardMode::Simple`.
+    pub reward_modes: Mapping<(PoolId, AccountId), RewardMode>,
+    pub unbonding: Mapping<AccountId, Vec<UnbondingEntry>>,
+    /// Principal owed to stakers per token, staked or unbonding, across all pools.
+    pub liabilities: Mapping<AccountId, Balance>,
+    /// Token rewards are paid in; may be the staking token of a pool.
+    #[lazy]
+    pub reward_token: AccountId,
+    #[lazy]
+    pub reward_source: RewardSource,
+    #[lazy]
+    pub reward_model: RewardModel,
+    /// Rewards per second split across pools by allocation weight under `RewardModel::RewardPerToken`.
+    #[lazy]
+    pub emission_rate: Balance,
+    /// Share of a locked withdrawal kept as penalty, in parts of `HUNDRED_PERCENT`.
+    /// Zero rejects withdrawals before unlock.
+    #[lazy]
+    pub early_unstake_penalty: u128,
+    /// Receives early-unstake penalties; they are burned when unset.
+    #[lazy]
+    pub treasury: Option<AccountId>,
+    /// Cooldown between `request_unstake` and `withdraw_unbonded`. Zero allows plain `unstake`.
+    #[lazy]
+    pub unbonding_period: Timestamp,
+    /// While set, only `withdraw_unbonded` and `emergency_withdraw` move funds.
+    #[lazy]
+    pub paused: bool,
+    /// Share of harvested rewards paid to the caller of `harvest_and_compound`, in parts of
+    /// `HUNDRED_PERCENT`.
+    #[lazy]
+    pub compound_bounty: u128,
+    /// Boost tiers by ascending lock duration; locks below the first tier are not boosted.
+    #[lazy]
+    pub boost_schedule: Vec<BoostTier>,
+    /// Sum of the boosted stakes of each pool, which `RewardModel::RewardPerToken` emissions
+    /// are split over. Absent for pools untouched since boosts were introduced.
+    pub boosted_supply: Mapping<PoolId, Balance>,
+    pub extra_reward_tokens: Mapping<PoolId, Vec<AccountId>>,
+    pub extra_rewards: Mapping<(PoolId, AccountId), ExtraReward>,
+    /// Extra reward `reward_per_token` already accounted for, keyed by (pool, reward token, account).
+    pub extra_reward_paid: Mapping<(PoolId, AccountId, AccountId), u128>,
+    /// Extra rewards accrued but not yet claimed, keyed by (pool, reward token, account).
+    pub extra_rewards_owed: Mapping<(PoolId, AccountId, AccountId), Balance>,
+    /// Rewards settled but not paid out because the reward reserve ran short, keyed by
+    /// (pool, account). Paid out with the account's next claim.
+    pub unpaid_rewards: Mapping<(PoolId, AccountId), Balance>,
+}
+
+#[derive(scale::Decode, scale::Encode, Default, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Pool {
     pub token: AccountId,
+    /// Daily reward under `RewardModel::FixedRate`, in parts of `HUNDRED_PERCENT`.
+    pub reward_rate: Balance,
+    /// Share of `emission_rate` the pool receives under `RewardModel::RewardPerToken`.
+    pub allocation_weight: u32,
+    pub total_staked: Balance,
+    /// Rewards accrued per staked token, scaled by `REWARD_PRECISION`.
+    pub reward_per_token_stored: u128,
+    pub last_update_time: Timestamp,
 }
 
 #[derive(scale::Decode, scale::Encode, Default, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(
     feature = "std",
-    derive{scale_info::TypeInfo, ink::storage::traits::StorageLayout}
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
 )]
 pub struct StakeInfo {
     pub amount: Balance,
     pub timestamp: Timestamp,
+    /// `reward_per_token` already accounted for this stake.
+    pub reward_debt: u128,
+    /// Zero when the stake was never locked.
+    pub unlock_at: Timestamp,
+    /// Rewards accrued before the last top-up, not yet paid out.
+    pub pending_rewards: Balance,
+    /// Reward weight of the stake, in parts of `HUNDRED_PERCENT`, earned through its lock.
+    pub boost: u128,
+}
+
+/// Stake record of the first release.
+#[derive(scale::Decode, scale::Encode, Default, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct LegacyStakeInfo {
+    pub amount: Balance,
+    pub timestamp: Timestamp,
+}
+
+/// Stakes are stored tagged with their schema so that a later layout can add a variant and
+/// still read the records written before it.
+#[derive(scale::Decode, scale::Encode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum StakeRecord {
+    V1(StakeInfo),
+}
+
+/// Staked balance from `timestamp` until the next checkpoint.
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Checkpoint {
+    pub timestamp: Timestamp,
+    pub amount: Balance,
+}
+
+/// Daily reward rate in effect from `timestamp` until the next checkpoint.
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct RateCheckpoint {
+    pub timestamp: Timestamp,
+    pub reward_rate: Balance,
+    /// Sum of the rate over every second before `timestamp`.
+    pub cumulative_rate: u128,
+}
+
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Delegation {
+    pub delegatee: AccountId,
+    /// Whether rewards are paid to, and may be claimed by, `delegatee` as well.
+    pub rewards: bool,
+}
+
+#[derive(scale::Decode, scale::Encode, Default, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct UnbondingEntry {
+    pub pool_id: PoolId,
+    pub amount: Balance,
+    pub release_at: Timestamp,
+}
+
+/// Positions locked for at least `lock_duration` earn primary rewards on `multiplier` times
+/// their amount, in parts of `HUNDRED_PERCENT`. Each stake into a position re-prices its
+/// boost from the lock it has left, which it keeps until the next one or until it unlocks.
+/// `RewardModel::FixedRate` stops boosting at the unlock time by itself, while under
+/// `RewardModel::RewardPerToken` the position has to be settled, e.g. by `kick`.
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct BoostTier {
+    pub lock_duration: Timestamp,
+    pub multiplier: u128,
+}
+
+/// Incentive paid in an additional token on top of the primary rewards.
+#[derive(scale::Decode, scale::Encode, Default, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct ExtraReward {
+    /// Rewards per second shared pro-rata by the pool's stakers between `start` and `end`,
+    /// as far as `funded` covers them; emissions beyond the funding are dropped.
+    pub emission_rate: Balance,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    /// Funding not yet paid out.
+    pub funded: Balance,
+    /// Part of `funded` already accrued to stakers, which later emissions cannot use.
+    pub accrued: Balance,
+    pub reward_per_token_stored: u128,
+    pub last_update_time: Timestamp,
+}
+
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum RewardModel {
+    /// Every stake earns its pool's `reward_rate` per day, without a global budget.
+    #[default]
+    FixedRate,
+    /// `emission_rate` per second is split across pools by allocation weight and
+    /// across each pool's stakers by their share of its `total_staked`.
+    RewardPerToken,
+}
+
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum RewardMode {
+    /// Rewards accrue on the principal alone and are paid out when claimed.
+    #[default]
+    Simple,
+    /// Rewards are folded into the principal on every interaction and grow daily in between
+    /// under `RewardModel::FixedRate`. Only available when the pool stakes the reward token.
+    Compound,
+}
+
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum RewardSource {
+    /// Rewards come from the reward reserve, minting whatever it cannot cover.
+    #[default]
+    Mint,
+    /// Rewards come only from the reward reserve funded through `fund_rewards`.
+    Reserve,
 }
 
-const HUNDRED_PERCENT: u128 = 1000000000;
-const INTEREST: u128 = 1000000; // 0.1%
-const ONE_DAY: Timestamp = 86400
+#[derive(scale::Decode, scale::Encode, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum StakingParameter {
+    RewardRate {
+        pool_id: PoolId,
+        reward_rate: Balance,
+    },
+    AllocationWeight {
+        pool_id: PoolId,
+        allocation_weight: u32,
+    },
+    EmissionRate(Balance),
+    EarlyUnstakePenalty(u128),
+    Treasury(Option<AccountId>),
+    UnbondingPeriod(Timestamp),
+    RewardSource(RewardSource),
+    CompoundBounty(u128),
+    BoostSchedule(Vec<BoostTier>),
+    ExtraReward {
+        pool_id: PoolId,
+        token: AccountId,
+        emission_rate: Balance,
+        start: Timestamp,
+        end: Timestamp,
+    },
+    /// The staking clock, see `StakingImpl::block_timestamp`.
+    Timestamp(Timestamp),
+}
+
+pub type PoolId = u32;
+pub type PositionId = u32;
+
+/// Layout written by this code; zero is the first release's. Bump together with a new
+/// `StakeRecord` variant.
+pub const STORAGE_VERSION: u32 = 1;
+/// Daily rate the first release paid on every stake: 0.1%.
+pub const LEGACY_REWARD_RATE: Balance = 1000000;
+pub const HUNDRED_PERCENT: u128 = 1000000000;
+pub const ONE_DAY: Timestamp = 86400;
+pub const REWARD_PRECISION: u128 = 1000000000000;
+pub const MAX_POOLS: PoolId = 32;
+pub const MAX_UNBONDING_ENTRIES: usize = 32;
+pub const MAX_POSITIONS: usize = 16;
+pub const MAX_EXTRA_REWARDS: usize = 8;
+/// Accounts a single `harvest_and_compound` call may process.
+pub const MAX_HARVEST_ACCOUNTS: usize = 32;
+/// Upper bound of `compound_bounty`: 10%.
+pub const MAX_COMPOUND_BOUNTY: u128 = HUNDRED_PERCENT / 10;
+pub const MAX_BOOST_TIERS: usize = 8;
+/// Upper bound of a boost multiplier: 10x.
+pub const MAX_BOOST: u128 = 10 * HUNDRED_PERCENT;
+/// Pool created by the constructor.
+pub const DEFAULT_POOL: PoolId = 0;
+/// Position used by `stake`, `unstake` and `request_unstake`.
+pub const DEFAULT_POSITION: PositionId = 0;
 
 pub trait StakingImpl: Storage<StakingData> {
-    fn stake_impl(&mut self, amount: Balance) -> Result<(), StakingError> {
+    fn create_pool_impl(
+        &mut self,
+        token: AccountId,
+        reward_rate: Balance,
+        allocation_weight: u32,
+    ) -> Result<PoolId, StakingError> {
+        let pool_id = self.pool_count_impl();
+        if pool_id >= MAX_POOLS {
+            return Err(StakingError::TooManyPools);
+        }
+
+        self._update_all_pools()?;
+
+        let pool = Pool {
+            token,
+            reward_rate,
+            allocation_weight,
+            last_update_time: self.block_timestamp(),
+            ..Default::default()
+        };
+        self.data().pools.insert(&pool_id, &pool);
+        let pool_count = pool_id.checked_add(1).ok_or(MathError::Overflow)?;
+        self.data().pool_count.set(&pool_count);
+        let total_allocation_weight = self
+            .total_allocation_weight()
+            .checked_add(allocation_weight)
+            .ok_or(MathError::Overflow)?;
+        self.data().total_allocation_weight.set(&total_allocation_weight);
+        self._emit_pool_created_event(pool_id, token, reward_rate, allocation_weight);
+
+        Ok(pool_id)
+    }
+
+    fn _pool(&self, pool_id: PoolId) -> Result<Pool, StakingError> {
+        self.data().pools.get(&pool_id).ok_or(StakingError::PoolNotFound)
+    }
+
+    fn stake_impl(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError> {
+        self.stake_locked_impl(pool_id, amount, 0)
+    }
+
+    /// Stakes `amount` and locks the whole position for at least `lock_duration`.
+    fn stake_locked_impl(&mut self, pool_id: PoolId, amount: Balance, lock_duration: Timestamp) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        self._stake_into(pool_id, caller, DEFAULT_POSITION, amount, lock_duration)
+    }
+
+    /// Opens a new position next to the caller's existing ones in the pool and returns its id.
+    fn open_position_impl(
+        &mut self,
+        pool_id: PoolId,
+        amount: Balance,
+        lock_duration: Timestamp,
+    ) -> Result<PositionId, StakingError> {
+        let caller = Self::env().caller();
+        let position_id = self._next_position_id(pool_id, caller)?;
+        self._stake_into(pool_id, caller, position_id, amount, lock_duration)?;
+
+        Ok(position_id)
+    }
+
+    /// Reserves a fresh position id for `account` in the pool.
+    fn _next_position_id(&mut self, pool_id: PoolId, account: AccountId) -> Result<PositionId, StakingError> {
+        let key = (pool_id, account);
+        if self.data().positions.get(&key).unwrap_or_default().len() >= MAX_POSITIONS {
+            return Err(StakingError::TooManyPositions);
+        }
+
+        let position_id = self
+            .data()
+            .last_position_id
+            .get(&key)
+            .unwrap_or_default()
+            .checked_add(1)
+            .ok_or(MathError::Overflow)?;
+        self.data().last_position_id.insert(&key, &position_id);
+        Ok(position_id)
+    }
+
+    fn top_up_impl(&mut self, pool_id: PoolId, position_id: PositionId, amount: Balance) -> Result<(), StakingError> {
         let caller = Self::env().caller();
-        if let Some(staker) = self.data().stakes.get(&caller) {
+        if self.get_position_impl(pool_id, caller, position_id).is_none() {
+            return Err(StakingError::NoStake);
+        }
+        self._stake_into(pool_id, caller, position_id, amount, 0)
+    }
+
+    fn _stake_into(
+        &mut self,
+        pool_id: PoolId,
+        account: AccountId,
+        position_id: PositionId,
+        amount: Balance,
+        lock_duration: Timestamp,
+    ) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        self._compound_position(pool_id, account, position_id)?;
+        let pool = self._pool(pool_id)?;
+        let mode = self.reward_mode_impl(pool_id, account);
+        let requested_unlock = if lock_duration > 0 {
+            math::add_duration(self.block_timestamp(), lock_duration)?
+        } else {
+            0
+        };
+        let requested_boost = self.boost_for_impl(lock_duration);
 
-            let accumulated = self.accumulated_rewards(&staker);
+        if let Some(staker) = self.get_position_impl(pool_id, account, position_id) {
+            // the reward clock restarts for the new amount, so what accrued so far is
+            // carried over instead of being lost or paid on the larger stake
+            let unlock_at = staker.unlock_at.max(requested_unlock);
             let new_info = StakeInfo {
-                amount: staker.amount * accumulated + amount,
-                timestamp: self.block_timestamp()
-            }
-            self.data().stakes.insert(&caller, &new_info);
-            
+                amount: math::add(staker.amount, amount)?,
+                timestamp: self.block_timestamp(),
+                reward_debt: self.reward_per_token(pool_id, &pool)?,
+                unlock_at,
+                pending_rewards: self.earned(pool_id, &pool, &staker, mode)?,
+                // boosted only as far as the remaining lock earns, so a top-up near or
+                // past the unlock time cannot inherit the boost of the original lock
+                boost: self.boost_for_impl(unlock_at.saturating_sub(self.block_timestamp())),
+            };
+            self._update_stake(pool_id, account, position_id, Some(new_info))?;
         } else {
             let new_info = StakeInfo {
                 amount,
-                timestamp: self.block_timestamp()
-            }
-            self.data().stakes.insert(&caller, &new_info);
+                timestamp: self.block_timestamp(),
+                reward_debt: self.reward_per_token(pool_id, &pool)?,
+                unlock_at: requested_unlock,
+                pending_rewards: 0,
+                boost: requested_boost,
+            };
+            self._update_stake(pool_id, account, position_id, Some(new_info))?;
         }
 
-        let token = self.data().token.get().ok_or(StakingError::TokenNotSet)?;
         let contract = Self::env().account_id();
-        PSP22Ref::transfer_from(&token, caller, contract, amount, Vec::default())?;
-        
-        OK(())
+        PSP22Ref::transfer_from(&pool.token, account, contract, amount, Vec::default())?;
+        self._emit_staked_event(pool_id, account, position_id, amount);
+
+        Ok(())
+    }
+
+    /// Everything the stake has earned and not been paid: carried rewards plus the accrual
+    /// since its clock last restarted.
+    fn earned(&self, pool_id: PoolId, pool: &Pool, stake_info: &StakeInfo, mode: RewardMode) -> Result<Balance, StakingError> {
+        Ok(math::add(stake_info.pending_rewards, self.accumulated_rewards(pool_id, pool, stake_info, mode)?)?)
+    }
+
+    /// Rewards accrued since the stake's clock last restarted, weighted by the stake's boost.
+    fn accumulated_rewards(&self, pool_id: PoolId, pool: &Pool, stake_info: &StakeInfo, mode: RewardMode) -> Result<Balance, StakingError> {
+        let reward = match self.reward_model_impl() {
+            RewardModel::FixedRate if mode == RewardMode::Compound => {
+                let elapsed = math::elapsed(stake_info.timestamp, self.block_timestamp())?;
+                if elapsed == 0 {
+                    return Ok(0);
+                }
+                // days compound at the period's average boosted rate, which is the pool's
+                // rate unless it or the boost changed since the stake's clock restarted
+                let rate_seconds = self._boosted_rate_seconds(pool_id, pool, stake_info)?;
+                let reward_rate = math::mul_div(rate_seconds, 1, elapsed, Rounding::Down)?;
+                let days = (elapsed / ONE_DAY as u128) as u64;
+                let growth = math::pow(math::add(HUNDRED_PERCENT, reward_rate)?, days, HUNDRED_PERCENT)?;
+                let grown = math::mul_div(stake_info.amount, growth, HUNDRED_PERCENT, Rounding::Down)?;
+                // the started day accrues linearly on the grown amount
+                let per_day = math::mul(grown, reward_rate)?;
+                let partial_day = math::mul_div(elapsed % ONE_DAY as u128, per_day, ONE_DAY as u128 * HUNDRED_PERCENT, Rounding::Down)?;
+                math::sub(math::add(grown, partial_day)?, stake_info.amount)?
+            }
+            RewardModel::FixedRate => {
+                let rate_seconds = self._boosted_rate_seconds(pool_id, pool, stake_info)?;
+                math::mul_div(stake_info.amount, rate_seconds, ONE_DAY as u128 * HUNDRED_PERCENT, Rounding::Down)?
+            }
+            RewardModel::RewardPerToken => {
+                let earned_per_token = math::sub(self.reward_per_token(pool_id, pool)?, stake_info.reward_debt)?;
+                math::mul_div(self._boosted(stake_info)?, earned_per_token, REWARD_PRECISION, Rounding::Down)?
+            }
+        };
+        Ok(reward)
+    }
+
+    /// Sum of the pool's `reward_rate` over every second since the stake's clock restarted,
+    /// each second at the rate in effect at the time and weighted by the stake's boost until
+    /// it unlocks.
+    fn _boosted_rate_seconds(&self, pool_id: PoolId, pool: &Pool, stake_info: &StakeInfo) -> Result<u128, StakingError> {
+        let now = self.block_timestamp();
+        let boosted_until = stake_info.unlock_at.max(stake_info.timestamp).min(now);
+
+        let start = self._cumulative_rate(pool_id, pool, stake_info.timestamp)?;
+        let unlock = self._cumulative_rate(pool_id, pool, boosted_until)?;
+        let end = self._cumulative_rate(pool_id, pool, now)?;
+        let boosted = math::mul_div(math::sub(unlock, start)?, stake_info.boost, HUNDRED_PERCENT, Rounding::Down)?;
+        Ok(math::add(boosted, math::sub(end, unlock)?)?)
+    }
+
+    /// Sum of the pool's `reward_rate` over every second before `timestamp`.
+    fn _cumulative_rate(&self, pool_id: PoolId, pool: &Pool, timestamp: Timestamp) -> Result<u128, StakingError> {
+        let count = self.data().rate_checkpoint_count.get(&pool_id).unwrap_or_default();
+        let checkpoint = self
+            ._checkpoint_index_at(count, timestamp, |index| {
+                self.data().rate_checkpoints.get(&(pool_id, index)).map(|checkpoint| checkpoint.timestamp)
+            })
+            .and_then(|index| self.data().rate_checkpoints.get(&(pool_id, index)))
+            // the rate never changed
+            .unwrap_or(RateCheckpoint {
+                timestamp: 0,
+                reward_rate: pool.reward_rate,
+                cumulative_rate: 0,
+            });
+        let elapsed = math::elapsed(checkpoint.timestamp, timestamp)?;
+        Ok(math::add(checkpoint.cumulative_rate, math::mul(elapsed, checkpoint.reward_rate)?)?)
     }
 
-    fn accumulated_rewards(&self, stake_info: &StakeInfo) -> Balance {
-        let current_time = self.block_timestamp();
-        let started = stake_info.timestamp;
-        let elapsed: u128 = (current_time - started) as u128;
-        let per_day: u128 = stake_info.amount * INTEREST;
-        let reward: u128 = ((elapsed * per_day) / ONE_DAY as u128) / HUNDRED_PERCENT;
-        reward as u128;
+    /// Current value of the pool's accumulator, including emissions since `last_update_time`.
+    /// Emissions are split by boosted stake.
+    fn reward_per_token(&self, pool_id: PoolId, pool: &Pool) -> Result<u128, StakingError> {
+        let boosted_supply = self.boosted_supply_impl(pool_id);
+        if boosted_supply == 0 {
+            return Ok(pool.reward_per_token_stored);
+        }
+
+        let elapsed = math::elapsed(pool.last_update_time, self.block_timestamp())?;
+        let emitted = math::mul(elapsed, self.pool_emission_rate(pool)?)?;
+        let per_token = math::mul_div(emitted, REWARD_PRECISION, boosted_supply, Rounding::Down)?;
+        Ok(math::add(pool.reward_per_token_stored, per_token)?)
+    }
+
+    /// The pool's share of `emission_rate`.
+    fn pool_emission_rate(&self, pool: &Pool) -> Result<Balance, StakingError> {
+        let total_allocation_weight = self.total_allocation_weight();
+        if total_allocation_weight == 0 {
+            return Ok(0);
+        }
+        Ok(math::mul_div(
+            self.emission_rate_impl(),
+            pool.allocation_weight as u128,
+            total_allocation_weight as u128,
+            Rounding::Down,
+        )?)
+    }
+
+    /// Checkpoints the pool's accumulator; must run before its `total_staked` or emission share change.
+    fn _update_pool(&mut self, pool_id: PoolId) -> Result<Pool, StakingError> {
+        let mut pool = self._pool(pool_id)?;
+        pool.reward_per_token_stored = self.reward_per_token(pool_id, &pool)?;
+        pool.last_update_time = self.block_timestamp();
+        self.data().pools.insert(&pool_id, &pool);
+        Ok(pool)
+    }
+
+    /// Checkpoints every pool; must run before `emission_rate` or any allocation weight change.
+    fn _update_all_pools(&mut self) -> Result<(), StakingError> {
+        for pool_id in 0..self.pool_count_impl() {
+            self._update_pool(pool_id)?;
+        }
+        Ok(())
     }
 
-    fn unstake_impl(&mut self, amount: Balance) -> Result<(), StakingError> {
-        
+    fn unstake_impl(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        if self.unbonding_period_impl() > 0 {
+            return Err(StakingError::UnbondingRequired);
+        }
+
         let caller = Self::env().caller();
-        if let Some(staker) = self.data().stakes.get(&caller) {
+        self._compound_position(pool_id, caller, DEFAULT_POSITION)?;
+        let pool = self._pool(pool_id)?;
+        let staker = self
+            .get_position_impl(pool_id, caller, DEFAULT_POSITION)
+            .ok_or(StakingError::NoStake)?;
+        if amount > staker.amount {
+            return Err(StakingError::GreaterAmountRequested);
+        }
 
-            let accumulated = self.accumulated_rewards(&staker);
-            let available = staker.amount * accumulated;
+        let locked = self.block_timestamp() < staker.unlock_at;
+        if locked && self.early_unstake_penalty_impl() == 0 {
+            return Err(StakingError::StillLocked);
+        }
 
-            if amount > available {
-                return Err(StakingError::GreaterAmountRequested);
-            } else if amount == available {
-                self.data().stakes.remove(&caller);
-            } else {
-                let new_info = StakeInfo {
-                    amount: available - amount,
-                    timestamp: self.block_timestamp();
-                }
+        let rewards = self.earned(pool_id, &pool, &staker, self.reward_mode_impl(pool_id, caller))?;
+        let remaining = math::sub(staker.amount, amount)?;
+        if remaining == 0 {
+            self._update_stake(pool_id, caller, DEFAULT_POSITION, None)?;
+        } else {
+            let new_info = StakeInfo {
+                amount: remaining,
+                timestamp: self.block_timestamp(),
+                reward_debt: self.reward_per_token(pool_id, &pool)?,
+                unlock_at: staker.unlock_at,
+                pending_rewards: 0,
+                boost: staker.boost,
+            };
+            self._update_stake(pool_id, caller, DEFAULT_POSITION, Some(new_info))?;
+        }
+
+        let payout = if locked {
+            self._deduct_penalty(pool_id, caller, amount)?
+        } else {
+            amount
+        };
+        self._transfer_principal(pool.token, caller, payout)?;
+        self._emit_unstaked_event(pool_id, caller, payout);
+
+        self._pay_rewards(pool_id, caller, rewards)?;
+
+        Ok(())
+    }
+
+    /// Moves `amount` of principal into the caller's unbonding queue, paying out the rewards
+    /// accrued so far. Queued funds stop earning and become withdrawable after `unbonding_period`.
+    fn request_unstake_impl(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        self._request_unstake_from(pool_id, caller, DEFAULT_POSITION, amount)
+    }
+
+    fn _request_unstake_from(
+        &mut self,
+        pool_id: PoolId,
+        account: AccountId,
+        position_id: PositionId,
+        amount: Balance,
+    ) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        self._compound_position(pool_id, account, position_id)?;
+        let pool = self._pool(pool_id)?;
+        let mut staker = self
+            .get_position_impl(pool_id, account, position_id)
+            .ok_or(StakingError::NoStake)?;
+        if amount > staker.amount {
+            return Err(StakingError::GreaterAmountRequested);
+        }
+
+        let locked = self.block_timestamp() < staker.unlock_at;
+        if locked && self.early_unstake_penalty_impl() == 0 {
+            return Err(StakingError::StillLocked);
+        }
+
+        let mut entries = self.data().unbonding.get(&account).unwrap_or_default();
+        if entries.len() >= MAX_UNBONDING_ENTRIES {
+            return Err(StakingError::TooManyUnbondingEntries);
+        }
+
+        let rewards = self.earned(pool_id, &pool, &staker, self.reward_mode_impl(pool_id, account))?;
+        staker.amount = math::sub(staker.amount, amount)?;
+        if staker.amount == 0 {
+            self._update_stake(pool_id, account, position_id, None)?;
+        } else {
+            staker.timestamp = self.block_timestamp();
+            staker.reward_debt = self.reward_per_token(pool_id, &pool)?;
+            staker.pending_rewards = 0;
+            self._update_stake(pool_id, account, position_id, Some(staker))?;
+        }
+
+        let queued = if locked {
+            self._deduct_penalty(pool_id, account, amount)?
+        } else {
+            amount
+        };
+        let release_at = math::add_duration(self.block_timestamp(), self.unbonding_period_impl())?;
+        entries.push(UnbondingEntry {
+            pool_id,
+            amount: queued,
+            release_at,
+        });
+        self.data().unbonding.insert(&account, &entries);
+        self._add_liability(pool.token, queued)?;
+        self._emit_unbonding_started_event(pool_id, account, queued, release_at);
+
+        self._pay_rewards(pool_id, account, rewards)?;
+
+        Ok(())
+    }
+
+    /// Pays out every unbonding entry of the caller whose cooldown has passed, in any pool.
+    fn withdraw_unbonded_impl(&mut self) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        let now = self.block_timestamp();
+        let entries = self.data().unbonding.get(&caller).unwrap_or_default();
+
+        let (released, pending): (Vec<UnbondingEntry>, Vec<UnbondingEntry>) =
+            entries.into_iter().partition(|entry| entry.release_at <= now);
+        if released.is_empty() {
+            return Err(StakingError::NothingToWithdraw);
+        }
+
+        if pending.is_empty() {
+            self.data().unbonding.remove(&caller);
+        } else {
+            self.data().unbonding.insert(&caller, &pending);
+        }
+
+        for entry in released {
+            let pool = self._pool(entry.pool_id)?;
+            self._sub_liability(pool.token, entry.amount)?;
+            self._transfer_principal(pool.token, caller, entry.amount)?;
+            self._emit_unstaked_event(entry.pool_id, caller, entry.amount);
+        }
+
+        Ok(())
+    }
+
+    /// Withdraws the whole position together with its rewards, going through the
+    /// unbonding queue when a cooldown is configured.
+    fn close_position_impl(&mut self, pool_id: PoolId, position_id: PositionId) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        let caller = Self::env().caller();
+        self._compound_position(pool_id, caller, position_id)?;
+        let pool = self._pool(pool_id)?;
+        let staker = self
+            .get_position_impl(pool_id, caller, position_id)
+            .ok_or(StakingError::NoStake)?;
+
+        if self.unbonding_period_impl() > 0 {
+            self._request_unstake_from(pool_id, caller, position_id, staker.amount)?;
+            self._emit_position_closed_event(pool_id, caller, position_id);
+            return Ok(());
+        }
+
+        let locked = self.block_timestamp() < staker.unlock_at;
+        if locked && self.early_unstake_penalty_impl() == 0 {
+            return Err(StakingError::StillLocked);
+        }
+
+        let rewards = self.earned(pool_id, &pool, &staker, self.reward_mode_impl(pool_id, caller))?;
+        self._update_stake(pool_id, caller, position_id, None)?;
+
+        let payout = if locked {
+            self._deduct_penalty(pool_id, caller, staker.amount)?
+        } else {
+            staker.amount
+        };
+        self._transfer_principal(pool.token, caller, payout)?;
+        self._emit_unstaked_event(pool_id, caller, payout);
+
+        self._pay_rewards(pool_id, caller, rewards)?;
+        self._emit_position_closed_event(pool_id, caller, position_id);
+
+        Ok(())
+    }
 
+    fn claim_impl(&mut self, pool_id: PoolId) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        self._claim(pool_id, caller)
+    }
+
+    /// Claims `account`'s rewards on behalf of the account it delegated its rewards to.
+    fn claim_for_impl(&mut self, pool_id: PoolId, account: AccountId) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        if caller == account || self._reward_recipient(account) != caller {
+            return Err(StakingError::NotDelegated);
+        }
+        self._claim(pool_id, account)
+    }
+
+    /// Claims the rewards of every position of `account` in the pool in a single payout,
+    /// together with everything accrued in the pool's extra reward tokens. Compounding
+    /// accounts get their primary rewards folded into principal instead.
+    fn _claim(&mut self, pool_id: PoolId, account: AccountId) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        let pool = self._pool(pool_id)?;
+        let mode = self.reward_mode_impl(pool_id, account);
+        let position_ids = self.data().positions.get(&(pool_id, account)).unwrap_or_default();
+        let has_positions = !position_ids.is_empty();
+        let has_unpaid = self.data().unpaid_rewards.get(&(pool_id, account)).unwrap_or_default() > 0;
+
+        let mut rewards: Balance = 0;
+        for position_id in position_ids {
+            if mode == RewardMode::Compound {
+                self._compound_position(pool_id, account, position_id)?;
+                continue;
             }
-            let token = self.data().token.get().ok_or(StakingError::TokenNotSet)?;
+            if let Some(mut staker) = self.get_position_impl(pool_id, account, position_id) {
+                rewards = math::add(rewards, self.earned(pool_id, &pool, &staker, mode)?)?;
+                staker.timestamp = self.block_timestamp();
+                staker.reward_debt = self.reward_per_token(pool_id, &pool)?;
+                staker.pending_rewards = 0;
+                self._update_stake(pool_id, account, position_id, Some(staker))?;
+            }
+        }
 
-            let contract_balance = PSP22Ref::balance_of(&token, Self::env().account_id());
-            if contract_balance>= amount {
-                PSP22Ref::transfer(&token, caller, amount, Vec::default())?;
-            } else {
-                let to_mint = amount - contract_balance;
-                if contract_balance > 0 {
-                    PSP22Ref::transfer(&token, caller, contract_balance, Vec::default())?;
-                }
-                PSP22Mintable::mint(&token, caller, to_mint)?;
+        self._pay_rewards(pool_id, account, rewards)?;
+
+        let claimed_extra = self._claim_extra_rewards(pool_id, account)?;
+        if !has_positions && !has_unpaid && !claimed_extra {
+            return Err(StakingError::NoStake);
+        }
+
+        Ok(())
+    }
+
+    /// Folds everything a compounding account has earned in the pool into the principal
+    /// of its positions. Anyone may trigger it.
+    fn compound_impl(&mut self, pool_id: PoolId, account: AccountId) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        if self.reward_mode_impl(pool_id, account) != RewardMode::Compound {
+            return Err(StakingError::NotCompounding);
+        }
+
+        let position_ids = self.data().positions.get(&(pool_id, account)).unwrap_or_default();
+        if position_ids.is_empty() {
+            return Err(StakingError::NoStake);
+        }
+        for position_id in position_ids {
+            self._compound_position(pool_id, account, position_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compounds every position of each listed account that compounds in the pool, and pays
+    /// the caller `compound_bounty` of the harvested rewards. Other accounts are skipped so one
+    /// stale entry does not fail the batch. Returns the bounty paid; what the reward reserve
+    /// cannot cover is owed to the caller like any other unpaid reward.
+    fn harvest_and_compound_impl(&mut self, pool_id: PoolId, accounts: Vec<AccountId>) -> Result<Balance, StakingError> {
+        self._ensure_not_paused()?;
+        if accounts.len() > MAX_HARVEST_ACCOUNTS {
+            return Err(StakingError::TooManyAccounts);
+        }
+        self._pool(pool_id)?;
+
+        let bounty_rate = self.compound_bounty_impl();
+        let mut bounty: Balance = 0;
+        for account in accounts {
+            let position_ids = self.data().positions.get(&(pool_id, account)).unwrap_or_default();
+            for position_id in position_ids {
+                bounty = math::add(bounty, self._harvest_position(pool_id, account, position_id, bounty_rate)?)?;
             }
-        
-        } 
-        
-        OK(())
+        }
+
+        if bounty == 0 {
+            return Ok(0);
+        }
+        let caller = Self::env().caller();
+        let paid = self._pay_out_rewards(caller, bounty)?;
+        self._owe_rewards(pool_id, caller, math::sub(bounty, paid)?)?;
+        if paid > 0 {
+            self._emit_compound_bounty_paid_event(pool_id, caller, paid);
+        }
+
+        Ok(paid)
+    }
+
+    /// Settles a position whose lock has ended and drops it back to a 1x boost, so it stops
+    /// taking a boosted share of the pool's `RewardModel::RewardPerToken` emissions.
+    fn kick_impl(&mut self, pool_id: PoolId, account: AccountId, position_id: PositionId) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        let pool = self._pool(pool_id)?;
+        let mut staker = self
+            .get_position_impl(pool_id, account, position_id)
+            .ok_or(StakingError::NoStake)?;
+        if staker.boost <= HUNDRED_PERCENT {
+            return Err(StakingError::NotBoosted);
+        }
+        if self.block_timestamp() < staker.unlock_at {
+            return Err(StakingError::StillLocked);
+        }
+
+        // `_update_stake` drops the expired boost once the earnings are carried over
+        staker.pending_rewards = self.earned(pool_id, &pool, &staker, self.reward_mode_impl(pool_id, account))?;
+        staker.timestamp = self.block_timestamp();
+        staker.reward_debt = self.reward_per_token(pool_id, &pool)?;
+        self._update_stake(pool_id, account, position_id, Some(staker))?;
+        self._emit_boost_expired_event(pool_id, account, position_id);
+
+        Ok(())
+    }
+
+    /// Folds the position's earnings into its principal when `account` compounds in the pool.
+    fn _compound_position(&mut self, pool_id: PoolId, account: AccountId, position_id: PositionId) -> Result<(), StakingError> {
+        self._harvest_position(pool_id, account, position_id, 0).map(|_| ())
+    }
+
+    /// Compounds the position after setting aside `bounty_rate` of its earnings, in parts of
+    /// `HUNDRED_PERCENT`, for whoever triggered it. Returns the amount set aside, which the
+    /// caller still has to pay out.
+    fn _harvest_position(
+        &mut self,
+        pool_id: PoolId,
+        account: AccountId,
+        position_id: PositionId,
+        bounty_rate: u128,
+    ) -> Result<Balance, StakingError> {
+        if self.reward_mode_impl(pool_id, account) != RewardMode::Compound {
+            return Ok(0);
+        }
+        let Some(mut staker) = self.get_position_impl(pool_id, account, position_id) else {
+            return Ok(0);
+        };
+
+        let pool = self._pool(pool_id)?;
+        let rewards = self.earned(pool_id, &pool, &staker, RewardMode::Compound)?;
+        if rewards == 0 {
+            return Ok(0);
+        }
+        let bounty = math::mul_div(rewards, bounty_rate, HUNDRED_PERCENT, Rounding::Down)?;
+        let compounded = math::sub(rewards, bounty)?;
+
+        let backed = self._back_compounded_rewards(compounded)?;
+        staker.amount = math::add(staker.amount, backed)?;
+        staker.timestamp = self.block_timestamp();
+        staker.reward_debt = self.reward_per_token(pool_id, &pool)?;
+        staker.pending_rewards = math::sub(compounded, backed)?;
+        self._update_stake(pool_id, account, position_id, Some(staker))?;
+        if backed > 0 {
+            self._emit_compounded_event(pool_id, account, position_id, backed);
+        }
+
+        Ok(bounty)
+    }
+
+    /// Makes sure the contract holds the rewards about to become principal and returns how
+    /// much of `amount` it does: the reward reserve covers it, under `RewardSource::Mint` the
+    /// shortfall is minted to the contract, and under `RewardSource::Reserve` only the reserve
+    /// is backed. Must run before the principal liabilities grow.
+    fn _back_compounded_rewards(&mut self, amount: Balance) -> Result<Balance, StakingError> {
+        let reserve = self.reward_reserve_impl();
+        if reserve >= amount {
+            return Ok(amount);
+        }
+        if self.reward_source_impl() == RewardSource::Reserve {
+            return Ok(reserve);
+        }
+
+        let reward_token = self.data().reward_token.get().ok_or(StakingError::TokenNotSet)?;
+        let contract = Self::env().account_id();
+        let to_mint = math::sub(amount, reserve)?;
+        PSP22MintableRef::mint(&reward_token, contract, to_mint)?;
+        self._emit_rewards_minted_event(contract, to_mint);
+
+        Ok(amount)
     }
 
+    /// Switches how the caller's rewards in the pool are handled. Rewards accrued so far are
+    /// settled under the previous mode first.
+    fn set_reward_mode_impl(&mut self, pool_id: PoolId, mode: RewardMode) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        let caller = Self::env().caller();
+        let pool = self._pool(pool_id)?;
+        if mode == RewardMode::Compound && self.reward_token_impl() != Some(pool.token) {
+            return Err(StakingError::CompoundingUnavailable);
+        }
+
+        let previous = self.reward_mode_impl(pool_id, caller);
+        let position_ids = self.data().positions.get(&(pool_id, caller)).unwrap_or_default();
+        for position_id in position_ids.iter().copied() {
+            if previous == RewardMode::Compound {
+                self._compound_position(pool_id, caller, position_id)?;
+            } else if let Some(mut staker) = self.get_position_impl(pool_id, caller, position_id) {
+                staker.pending_rewards = self.earned(pool_id, &pool, &staker, previous)?;
+                staker.timestamp = self.block_timestamp();
+                staker.reward_debt = self.reward_per_token(pool_id, &pool)?;
+                self._update_stake(pool_id, caller, position_id, Some(staker))?;
+            }
+        }
+
+        match mode {
+            RewardMode::Simple => self.data().reward_modes.remove(&(pool_id, caller)),
+            RewardMode::Compound => {
+                self.data().reward_modes.insert(&(pool_id, caller), &mode);
+                // carried rewards join the principal right away
+                for position_id in position_ids {
+                    self._compound_position(pool_id, caller, position_id)?;
+                }
+            }
+        }
+        self._emit_reward_mode_changed_event(pool_id, caller, mode);
+
+        Ok(())
+    }
+
+    fn reward_mode_impl(&self, pool_id: PoolId, account: AccountId) -> RewardMode {
+        self.data().reward_modes.get(&(pool_id, account)).unwrap_or_default()
+    }
+
+    /// Returns the caller's whole principal in the pool, closing all its positions and
+    /// forfeiting their rewards. Skips locks, penalties and the unbonding period, so it is
+    /// only open while the contract is paused, and never fails on reward accounting.
+    fn emergency_withdraw_impl(&mut self, pool_id: PoolId) -> Result<(), StakingError> {
+        if !self.paused_impl() {
+            return Err(StakingError::NotPaused);
+        }
+
+        let caller = Self::env().caller();
+        let mut pool = self._pool(pool_id)?;
+        let account_key = (pool_id, caller);
+        let position_ids = self.data().positions.get(&account_key).unwrap_or_default();
+        if position_ids.is_empty() {
+            return Err(StakingError::NoStake);
+        }
+
+        // checkpoints the accumulators at the old supply so that the other stakers keep
+        // what they earned; when that fails, principal still comes back
+        if let Ok(updated) = self._update_pool(pool_id) {
+            pool = updated;
+        }
+        self._update_extra_rewards(pool_id, None).ok();
+
+        let account_staked = self.data().account_staked.get(&account_key).unwrap_or_default();
+        let mut principal: Balance = 0;
+        let mut boosted: Balance = 0;
+        for position_id in position_ids {
+            if let Some(staker) = self.get_position_impl(pool_id, caller, position_id) {
+                principal = math::add(principal, staker.amount)?;
+                boosted = boosted.saturating_add(self._boosted(&staker).unwrap_or_default());
+            }
+            self.data().stake_records.remove(&(pool_id, caller, position_id));
+        }
+        self.data().positions.remove(&account_key);
+        self.data().account_staked.remove(&account_key);
+        for reward_token in self.extra_reward_tokens_impl(pool_id) {
+            self.data().extra_rewards_owed.remove(&(pool_id, reward_token, caller));
+        }
+
+        pool.total_staked = math::sub(pool.total_staked, principal)?;
+        self.data().pools.insert(&pool_id, &pool);
+        let boosted_supply = self.boosted_supply_impl(pool_id).saturating_sub(boosted);
+        self.data().boosted_supply.insert(&pool_id, &boosted_supply);
+        self._checkpoint_stake(pool_id, caller, 0)?;
+        self._checkpoint_total_staked(pool_id, pool.total_staked)?;
+        if pool_id == DEFAULT_POOL {
+            self._move_delegated_stake(caller, account_staked, 0)?;
+        }
+        self._sub_liability(pool.token, principal)?;
+        self._transfer_principal(pool.token, caller, principal)?;
+        self._emit_emergency_withdrawn_event(pool_id, caller, principal);
+
+        Ok(())
+    }
+
+    fn _ensure_not_paused(&self) -> Result<(), StakingError> {
+        if self.paused_impl() {
+            return Err(StakingError::Paused);
+        }
+        Ok(())
+    }
+
+    fn paused_impl(&self) -> bool {
+        self.data().paused.get().unwrap_or_default()
+    }
+
+    fn set_paused_impl(&mut self, paused: bool) -> Result<(), StakingError> {
+        if self.paused_impl() == paused {
+            return Err(StakingError::InvalidParameter);
+        }
+        self.data().paused.set(&paused);
+        self._emit_paused_changed_event(Self::env().caller(), paused);
+        Ok(())
+    }
+
+    /// Writes one of `account`'s positions in a pool, or removes it on `None`, keeping the
+    /// pool's `total_staked`, principal liabilities and the position index in line. A position
+    /// written after its unlock time loses its boost, so callers settle its earnings first.
+    fn _update_stake(
+        &mut self,
+        pool_id: PoolId,
+        account: AccountId,
+        position_id: PositionId,
+        stake_info: Option<StakeInfo>,
+    ) -> Result<(), StakingError> {
+        let now = self.block_timestamp();
+        let stake_info = stake_info.map(|mut staker| {
+            if staker.unlock_at <= now {
+                staker.boost = HUNDRED_PERCENT;
+            }
+            staker
+        });
+        let mut pool = self._update_pool(pool_id)?;
+        self._update_extra_rewards(pool_id, Some(account))?;
+
+        let key = (pool_id, account, position_id);
+        let previous = self.get_position_impl(pool_id, account, position_id);
+        let previous_amount = previous.as_ref().map(|staker| staker.amount).unwrap_or_default();
+        let new_amount = stake_info.as_ref().map(|staker| staker.amount).unwrap_or_default();
+
+        pool.total_staked = math::add(math::sub(pool.total_staked, previous_amount)?, new_amount)?;
+        self.data().pools.insert(&pool_id, &pool);
+        let previous_boosted = previous.as_ref().map(|staker| self._boosted(staker)).transpose()?.unwrap_or_default();
+        let new_boosted = stake_info.as_ref().map(|staker| self._boosted(staker)).transpose()?.unwrap_or_default();
+        let boosted_supply = math::add(math::sub(self.boosted_supply_impl(pool_id), previous_boosted)?, new_boosted)?;
+        self.data().boosted_supply.insert(&pool_id, &boosted_supply);
+        self._sub_liability(pool.token, previous_amount)?;
+        self._add_liability(pool.token, new_amount)?;
+
+        let account_key = (pool_id, account);
+        let previous_account_staked = self.data().account_staked.get(&account_key).unwrap_or_default();
+        let account_staked = math::add(math::sub(previous_account_staked, previous_amount)?, new_amount)?;
+        if account_staked == 0 {
+            self.data().account_staked.remove(&account_key);
+        } else {
+            self.data().account_staked.insert(&account_key, &account_staked);
+        }
+        if account_staked != previous_account_staked {
+            self._checkpoint_stake(pool_id, account, account_staked)?;
+            self._checkpoint_total_staked(pool_id, pool.total_staked)?;
+            if pool_id == DEFAULT_POOL {
+                self._move_delegated_stake(account, previous_account_staked, account_staked)?;
+            }
+        }
+
+        let mut position_ids = self.data().positions.get(&account_key).unwrap_or_default();
+        match stake_info {
+            Some(stake_info) => {
+                self.data().stake_records.insert(&key, &StakeRecord::V1(stake_info));
+                if previous.is_none() {
+                    position_ids.push(position_id);
+                    self.data().positions.insert(&account_key, &position_ids);
+                }
+            }
+            None => {
+                self.data().stake_records.remove(&key);
+                position_ids.retain(|id| *id != position_id);
+                if position_ids.is_empty() {
+                    self.data().positions.remove(&account_key);
+                } else {
+                    self.data().positions.insert(&account_key, &position_ids);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn _checkpoint_stake(&mut self, pool_id: PoolId, account: AccountId, amount: Balance) -> Result<(), StakingError> {
+        let count_key = (pool_id, account);
+        let count = self.data().stake_checkpoint_count.get(&count_key).unwrap_or_default();
+        let last = count.checked_sub(1).and_then(|index| {
+            self.data()
+                .stake_checkpoints
+                .get(&(pool_id, account, index))
+                .map(|checkpoint| (index, checkpoint.timestamp))
+        });
+        let (index, count) = self._next_checkpoint_index(count, last)?;
+        let checkpoint = Checkpoint { timestamp: self.block_timestamp(), amount };
+        self.data().stake_checkpoints.insert(&(pool_id, account, index), &checkpoint);
+        self.data().stake_checkpoint_count.insert(&count_key, &count);
+        Ok(())
+    }
+
+    fn _checkpoint_total_staked(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError> {
+        let count = self.data().total_staked_checkpoint_count.get(&pool_id).unwrap_or_default();
+        let last = count.checked_sub(1).and_then(|index| {
+            self.data()
+                .total_staked_checkpoints
+                .get(&(pool_id, index))
+                .map(|checkpoint| (index, checkpoint.timestamp))
+        });
+        let (index, count) = self._next_checkpoint_index(count, last)?;
+        let checkpoint = Checkpoint { timestamp: self.block_timestamp(), amount };
+        self.data().total_staked_checkpoints.insert(&(pool_id, index), &checkpoint);
+        self.data().total_staked_checkpoint_count.insert(&pool_id, &count);
+        Ok(())
+    }
+
+    /// Where to write a checkpoint for the current time and the resulting count. Changes
+    /// within one block overwrite each other so that timestamps stay strictly increasing.
+    fn _next_checkpoint_index(&self, count: u32, last: Option<(u32, Timestamp)>) -> Result<(u32, u32), StakingError> {
+        match last {
+            Some((index, timestamp)) if timestamp >= self.block_timestamp() => Ok((index, count)),
+            _ => Ok((count, count.checked_add(1).ok_or(MathError::Overflow)?)),
+        }
+    }
+
+    /// Amount of the last of `count` checkpoints taken at or before `timestamp`; zero before
+    /// the first one.
+    fn _checkpoint_at(&self, count: u32, timestamp: Timestamp, checkpoint: impl Fn(u32) -> Option<Checkpoint>) -> Balance {
+        self._checkpoint_index_at(count, timestamp, |index| checkpoint(index).map(|found| found.timestamp))
+            .and_then(|index| checkpoint(index))
+            .map(|found| found.amount)
+            .unwrap_or_default()
+    }
+
+    /// Index of the last of `count` checkpoints taken at or before `timestamp`, found by
+    /// binary search over their timestamps.
+    fn _checkpoint_index_at(&self, count: u32, timestamp: Timestamp, timestamp_of: impl Fn(u32) -> Option<Timestamp>) -> Option<u32> {
+        let (mut low, mut high) = (0, count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match timestamp_of(middle) {
+                Some(found) if found <= timestamp => low = middle + 1,
+                _ => high = middle,
+            }
+        }
+        low.checked_sub(1)
+    }
+
+    /// `account`'s stake in the pool at `timestamp`. History starts with the first change
+    /// after checkpoints were introduced.
+    fn stake_of_at_impl(&self, pool_id: PoolId, account: AccountId, timestamp: Timestamp) -> Balance {
+        let count = self.data().stake_checkpoint_count.get(&(pool_id, account)).unwrap_or_default();
+        self._checkpoint_at(count, timestamp, |index| {
+            self.data().stake_checkpoints.get(&(pool_id, account, index))
+        })
+    }
+
+    fn total_staked_at_impl(&self, pool_id: PoolId, timestamp: Timestamp) -> Balance {
+        let count = self.data().total_staked_checkpoint_count.get(&pool_id).unwrap_or_default();
+        self._checkpoint_at(count, timestamp, |index| {
+            self.data().total_staked_checkpoints.get(&(pool_id, index))
+        })
+    }
+
+    fn current_timestamp_impl(&self) -> Timestamp {
+        self.block_timestamp()
+    }
+
+    /// Hands the caller's governance weight to `delegatee`, and its rewards too when
+    /// `rewards` is set. Principal stays with the caller.
+    fn delegate_impl(&mut self, delegatee: AccountId, rewards: bool) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        if delegatee == caller {
+            return Err(StakingError::InvalidParameter);
+        }
+        self._set_delegation(caller, Some(Delegation { delegatee, rewards }))
+    }
+
+    fn undelegate_impl(&mut self) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        if self.delegates_impl(caller).is_none() {
+            return Err(StakingError::NotDelegated);
+        }
+        self._set_delegation(caller, None)
+    }
+
+    fn _set_delegation(&mut self, account: AccountId, delegation: Option<Delegation>) -> Result<(), StakingError> {
+        let previous = self.delegates_impl(account);
+        let stake = self._governance_stake(account);
+        if let Some(previous) = previous {
+            let received = math::sub(self._received_delegations(previous.delegatee), stake)?;
+            self.data().received_delegations.insert(&previous.delegatee, &received);
+            self._checkpoint_power(previous.delegatee)?;
+        }
+        match delegation {
+            Some(delegation) => {
+                self.data().delegations.insert(&account, &delegation);
+                let received = math::add(self._received_delegations(delegation.delegatee), stake)?;
+                self.data().received_delegations.insert(&delegation.delegatee, &received);
+                self._checkpoint_power(delegation.delegatee)?;
+            }
+            None => self.data().delegations.remove(&account),
+        }
+        self._checkpoint_power(account)?;
+        self._emit_delegate_changed_event(
+            account,
+            previous.map(|previous| previous.delegatee),
+            delegation.map(|delegation| delegation.delegatee),
+            delegation.map(|delegation| delegation.rewards).unwrap_or_default(),
+        );
+
+        Ok(())
+    }
+
+    /// Keeps the weight of `account`'s delegatee in line when its `DEFAULT_POOL` stake changes.
+    fn _move_delegated_stake(&mut self, account: AccountId, previous: Balance, new: Balance) -> Result<(), StakingError> {
+        if let Some(delegation) = self.delegates_impl(account) {
+            let received = math::add(math::sub(self._received_delegations(delegation.delegatee), previous)?, new)?;
+            self.data().received_delegations.insert(&delegation.delegatee, &received);
+            self._checkpoint_power(delegation.delegatee)?;
+        }
+        self._checkpoint_power(account)?;
+        Ok(())
+    }
+
+    fn delegates_impl(&self, account: AccountId) -> Option<Delegation> {
+        self.data().delegations.get(&account)
+    }
+
+    /// Governance weight of `account`: its own `DEFAULT_POOL` stake unless delegated away,
+    /// plus the stake delegated to it.
+    fn delegated_power_impl(&self, account: AccountId) -> Balance {
+        let own = match self.delegates_impl(account) {
+            Some(_) => 0,
+            None => self._governance_stake(account),
+        };
+        own.saturating_add(self._received_delegations(account))
+    }
+
+    /// `delegated_power` as of `timestamp`. History starts with the first change after
+    /// delegation was introduced.
+    fn delegated_power_at_impl(&self, account: AccountId, timestamp: Timestamp) -> Balance {
+        let count = self.data().power_checkpoint_count.get(&account).unwrap_or_default();
+        self._checkpoint_at(count, timestamp, |index| self.data().power_checkpoints.get(&(account, index)))
+    }
+
+    fn _governance_stake(&self, account: AccountId) -> Balance {
+        self.data().account_staked.get(&(DEFAULT_POOL, account)).unwrap_or_default()
+    }
+
+    fn _received_delegations(&self, account: AccountId) -> Balance {
+        self.data().received_delegations.get(&account).unwrap_or_default()
+    }
+
+    /// Where `account`'s rewards are paid.
+    fn _reward_recipient(&self, account: AccountId) -> AccountId {
+        match self.delegates_impl(account) {
+            Some(delegation) if delegation.rewards => delegation.delegatee,
+            _ => account,
+        }
+    }
+
+    fn _checkpoint_power(&mut self, account: AccountId) -> Result<(), StakingError> {
+        let count = self.data().power_checkpoint_count.get(&account).unwrap_or_default();
+        let last = count.checked_sub(1).and_then(|index| {
+            self.data()
+                .power_checkpoints
+                .get(&(account, index))
+                .map(|checkpoint| (index, checkpoint.timestamp))
+        });
+        let (index, count) = self._next_checkpoint_index(count, last)?;
+        let checkpoint = Checkpoint {
+            timestamp: self.block_timestamp(),
+            amount: self.delegated_power_impl(account),
+        };
+        self.data().power_checkpoints.insert(&(account, index), &checkpoint);
+        self.data().power_checkpoint_count.insert(&account, &count);
+        Ok(())
+    }
+
+    fn _add_liability(&mut self, token: AccountId, amount: Balance) -> Result<(), StakingError> {
+        let liability = math::add(self.liabilities_of(token), amount)?;
+        self.data().liabilities.insert(&token, &liability);
+        Ok(())
+    }
+
+    fn _sub_liability(&mut self, token: AccountId, amount: Balance) -> Result<(), StakingError> {
+        let liability = math::sub(self.liabilities_of(token), amount)?;
+        self.data().liabilities.insert(&token, &liability);
+        Ok(())
+    }
+
+    /// Principal in `token` the contract owes to stakers, staked or unbonding.
+    fn liabilities_of(&self, token: AccountId) -> Balance {
+        self.data().liabilities.get(&token).unwrap_or_default()
+    }
+
+    fn extra_reward_per_token(&self, pool: &Pool, extra_reward: &ExtraReward) -> Result<u128, StakingError> {
+        let emitted = self._extra_reward_emitted(pool, extra_reward)?;
+        if emitted == 0 {
+            return Ok(extra_reward.reward_per_token_stored);
+        }
+
+        let per_token = math::mul_div(emitted, REWARD_PRECISION, pool.total_staked, Rounding::Down)?;
+        Ok(math::add(extra_reward.reward_per_token_stored, per_token)?)
+    }
+
+    /// Extra rewards emitted to the pool's stakers since the last checkpoint, capped at the
+    /// funding not accrued yet so stakers are never owed more than the contract holds.
+    fn _extra_reward_emitted(&self, pool: &Pool, extra_reward: &ExtraReward) -> Result<Balance, StakingError> {
+        let until = self.block_timestamp().min(extra_reward.end);
+        let from = extra_reward.last_update_time.max(extra_reward.start);
+        if pool.total_staked == 0 || until <= from {
+            return Ok(0);
+        }
+
+        let emitted = math::mul(math::elapsed(from, until)?, extra_reward.emission_rate)?;
+        Ok(emitted.min(extra_reward.funded.saturating_sub(extra_reward.accrued)))
+    }
+
+    fn _earned_extra(
+        &self,
+        pool_id: PoolId,
+        reward_token: AccountId,
+        account: AccountId,
+        reward_per_token: u128,
+    ) -> Result<Balance, StakingError> {
+        let key = (pool_id, reward_token, account);
+        let paid = self.data().extra_reward_paid.get(&key).unwrap_or_default();
+        let staked = self.data().account_staked.get(&(pool_id, account)).unwrap_or_default();
+        let owed = self.data().extra_rewards_owed.get(&key).unwrap_or_default();
+        let earned = math::mul_div(staked, math::sub(reward_per_token, paid)?, REWARD_PRECISION, Rounding::Down)?;
+        Ok(math::add(owed, earned)?)
+    }
+
+    /// Checkpoints every extra reward accumulator of the pool and, when given, moves `account`'s
+    /// accrual into `extra_rewards_owed`. Must run before the pool's `total_staked` or the
+    /// account's stake in it change.
+    fn _update_extra_rewards(&mut self, pool_id: PoolId, account: Option<AccountId>) -> Result<(), StakingError> {
+        let Some(pool) = self.data().pools.get(&pool_id) else {
+            return Ok(());
+        };
+        let now = self.block_timestamp();
+        for reward_token in self.extra_reward_tokens_impl(pool_id) {
+            let Some(mut extra_reward) = self.data().extra_rewards.get(&(pool_id, reward_token)) else {
+                continue;
+            };
+            let emitted = self._extra_reward_emitted(&pool, &extra_reward)?;
+            extra_reward.reward_per_token_stored = self.extra_reward_per_token(&pool, &extra_reward)?;
+            extra_reward.accrued = math::add(extra_reward.accrued, emitted)?;
+            extra_reward.last_update_time = now;
+            self.data().extra_rewards.insert(&(pool_id, reward_token), &extra_reward);
+
+            if let Some(account) = account {
+                let key = (pool_id, reward_token, account);
+                let owed = self._earned_extra(pool_id, reward_token, account, extra_reward.reward_per_token_stored)?;
+                self.data().extra_rewards_owed.insert(&key, &owed);
+                self.data().extra_reward_paid.insert(&key, &extra_reward.reward_per_token_stored);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pays `account` what it is owed in every extra reward token of the pool, as far as
+    /// funding allows. Returns whether anything was paid.
+    fn _claim_extra_rewards(&mut self, pool_id: PoolId, account: AccountId) -> Result<bool, StakingError> {
+        self._update_extra_rewards(pool_id, Some(account))?;
+
+        let mut claimed = false;
+        for reward_token in self.extra_reward_tokens_impl(pool_id) {
+            let Some(mut extra_reward) = self.data().extra_rewards.get(&(pool_id, reward_token)) else {
+                continue;
+            };
+            let key = (pool_id, reward_token, account);
+            let owed = self.data().extra_rewards_owed.get(&key).unwrap_or_default();
+            let amount = owed.min(extra_reward.funded);
+            if amount == 0 {
+                continue;
+            }
+
+            extra_reward.funded = math::sub(extra_reward.funded, amount)?;
+            extra_reward.accrued = extra_reward.accrued.saturating_sub(amount);
+            self.data().extra_rewards.insert(&(pool_id, reward_token), &extra_reward);
+            if owed == amount {
+                self.data().extra_rewards_owed.remove(&key);
+            } else {
+                self.data().extra_rewards_owed.insert(&key, &math::sub(owed, amount)?);
+            }
+
+            PSP22Ref::transfer(&reward_token, self._reward_recipient(account), amount, Vec::default())?;
+            self._emit_extra_rewards_claimed_event(pool_id, account, reward_token, amount);
+            claimed = true;
+        }
+
+        Ok(claimed)
+    }
+
+    /// Registers `reward_token` as an extra reward of the pool or reschedules an existing one.
+    fn set_extra_reward_impl(
+        &mut self,
+        pool_id: PoolId,
+        reward_token: AccountId,
+        emission_rate: Balance,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Result<(), StakingError> {
+        let pool = self._pool(pool_id)?;
+        if start >= end || reward_token == pool.token || Some(reward_token) == self.data().reward_token.get() {
+            return Err(StakingError::InvalidParameter);
+        }
+
+        self._update_extra_rewards(pool_id, None)?;
+
+        let mut extra_reward = match self.data().extra_rewards.get(&(pool_id, reward_token)) {
+            Some(extra_reward) => extra_reward,
+            None => {
+                let mut reward_tokens = self.extra_reward_tokens_impl(pool_id);
+                if reward_tokens.len() >= MAX_EXTRA_REWARDS {
+                    return Err(StakingError::TooManyRewardTokens);
+                }
+                reward_tokens.push(reward_token);
+                self.data().extra_reward_tokens.insert(&pool_id, &reward_tokens);
+                ExtraReward {
+                    last_update_time: self.block_timestamp(),
+                    ..Default::default()
+                }
+            }
+        };
+        extra_reward.emission_rate = emission_rate;
+        extra_reward.start = start;
+        extra_reward.end = end;
+        self.data().extra_rewards.insert(&(pool_id, reward_token), &extra_reward);
+
+        self._emit_parameters_changed_event(
+            Self::env().caller(),
+            StakingParameter::ExtraReward {
+                pool_id,
+                token: reward_token,
+                emission_rate,
+                start,
+                end,
+            },
+        );
+        Ok(())
+    }
+
+    /// Transfers `amount` of `reward_token` from the caller into that extra reward's funding.
+    fn fund_extra_reward_impl(&mut self, pool_id: PoolId, reward_token: AccountId, amount: Balance) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        let mut extra_reward = self
+            .data()
+            .extra_rewards
+            .get(&(pool_id, reward_token))
+            .ok_or(StakingError::UnknownRewardToken)?;
+
+        PSP22Ref::transfer_from(&reward_token, caller, Self::env().account_id(), amount, Vec::default())?;
+        extra_reward.funded = math::add(extra_reward.funded, amount)?;
+        self.data().extra_rewards.insert(&(pool_id, reward_token), &extra_reward);
+        self._emit_extra_reward_funded_event(pool_id, caller, reward_token, amount);
+
+        Ok(())
+    }
+
+    fn extra_reward_tokens_impl(&self, pool_id: PoolId) -> Vec<AccountId> {
+        self.data().extra_reward_tokens.get(&pool_id).unwrap_or_default()
+    }
+
+    fn extra_reward_impl(&self, pool_id: PoolId, reward_token: AccountId) -> Option<ExtraReward> {
+        self.data().extra_rewards.get(&(pool_id, reward_token))
+    }
+
+    /// Tokens whose accrual cannot be computed are left out; claiming reports the error.
+    fn pending_extra_rewards_impl(&self, pool_id: PoolId, account: AccountId) -> Vec<(AccountId, Balance)> {
+        let Some(pool) = self.data().pools.get(&pool_id) else {
+            return Vec::new();
+        };
+        self.extra_reward_tokens_impl(pool_id)
+            .into_iter()
+            .filter_map(|reward_token| {
+                let extra_reward = self.data().extra_rewards.get(&(pool_id, reward_token))?;
+                let reward_per_token = self.extra_reward_per_token(&pool, &extra_reward).ok()?;
+                let earned = self._earned_extra(pool_id, reward_token, account, reward_per_token).ok()?;
+                Some((reward_token, earned))
+            })
+            .collect()
+    }
+
+    /// Returns principal that has already been taken off the books to its owner.
+    fn _transfer_principal(&mut self, token: AccountId, to: AccountId, amount: Balance) -> Result<(), StakingError> {
+        PSP22Ref::transfer(&token, to, amount, Vec::default())?;
+        Ok(())
+    }
+
+    /// Pays `account`'s rewards in the pool together with what earlier payouts left unpaid,
+    /// and keeps whatever the reward reserve cannot cover owed. Returns the amount paid.
+    fn _pay_rewards(&mut self, pool_id: PoolId, account: AccountId, rewards: Balance) -> Result<Balance, StakingError> {
+        let unpaid = self.data().unpaid_rewards.get(&(pool_id, account)).unwrap_or_default();
+        let owed = math::add(rewards, unpaid)?;
+        if owed == 0 {
+            return Ok(0);
+        }
+
+        self.data().unpaid_rewards.remove(&(pool_id, account));
+        let paid = self._pay_out_rewards(self._reward_recipient(account), owed)?;
+        self._owe_rewards(pool_id, account, math::sub(owed, paid)?)?;
+        if paid > 0 {
+            self._emit_rewards_claimed_event(pool_id, account, paid);
+        }
+
+        Ok(paid)
+    }
+
+    /// Records `amount` of rewards `account` is owed in the pool but could not be paid.
+    fn _owe_rewards(&mut self, pool_id: PoolId, account: AccountId, amount: Balance) -> Result<(), StakingError> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let unpaid = self.data().unpaid_rewards.get(&(pool_id, account)).unwrap_or_default();
+        self.data().unpaid_rewards.insert(&(pool_id, account), &math::add(unpaid, amount)?);
+        Ok(())
+    }
+
+    /// Pays rewards out of the reward reserve, never touching principal, and returns the
+    /// amount paid. Under `RewardSource::Mint` the shortfall is minted, while under
+    /// `RewardSource::Reserve` at most the reserve is paid.
+    fn _pay_out_rewards(&mut self, to: AccountId, amount: Balance) -> Result<Balance, StakingError> {
+        let reward_token = self.data().reward_token.get().ok_or(StakingError::TokenNotSet)?;
+
+        let reserve = self.reward_reserve_impl();
+        if reserve >= amount {
+            PSP22Ref::transfer(&reward_token, to, amount, Vec::default())?;
+            return Ok(amount);
+        }
+        if self.reward_source_impl() == RewardSource::Reserve {
+            if reserve > 0 {
+                PSP22Ref::transfer(&reward_token, to, reserve, Vec::default())?;
+            }
+            return Ok(reserve);
+        }
+
+        let to_mint = math::sub(amount, reserve)?;
+        if reserve > 0 {
+            PSP22Ref::transfer(&reward_token, to, reserve, Vec::default())?;
+        }
+        PSP22MintableRef::mint(&reward_token, to, to_mint)?;
+        self._emit_rewards_minted_event(to, to_mint);
+
+        Ok(amount)
+    }
+
+    fn fund_rewards_impl(&mut self, amount: Balance) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        let reward_token = self.data().reward_token.get().ok_or(StakingError::TokenNotSet)?;
+        PSP22Ref::transfer_from(&reward_token, caller, Self::env().account_id(), amount, Vec::default())?;
+        self._emit_rewards_funded_event(caller, amount);
+        Ok(())
+    }
+
+    /// Withdraws reward funds to `to`; principal owed to stakers cannot be withdrawn.
+    fn withdraw_excess_rewards_impl(&mut self, to: AccountId, amount: Balance) -> Result<(), StakingError> {
+        if amount > self.reward_reserve_impl() {
+            return Err(StakingError::InsufficientRewardReserve);
+        }
+        let reward_token = self.data().reward_token.get().ok_or(StakingError::TokenNotSet)?;
+        PSP22Ref::transfer(&reward_token, to, amount, Vec::default())?;
+        self._emit_excess_rewards_withdrawn_event(to, amount);
+        Ok(())
+    }
+
+    /// Reward token balance not owed to stakers as principal.
+    fn reward_reserve_impl(&self) -> Balance {
+        let Some(reward_token) = self.data().reward_token.get() else {
+            return 0;
+        };
+        let contract_balance = PSP22Ref::balance_of(&reward_token, Self::env().account_id());
+        contract_balance.saturating_sub(self.liabilities_of(reward_token))
+    }
+
+    /// Charges the early-unstake penalty on `amount` and returns what is left for `account`.
+    fn _deduct_penalty(&mut self, pool_id: PoolId, account: AccountId, amount: Balance) -> Result<Balance, StakingError> {
+        let penalty = math::mul_div(amount, self.early_unstake_penalty_impl(), HUNDRED_PERCENT, Rounding::Up)?;
+        self._charge_penalty(pool_id, account, penalty)?;
+        Ok(math::sub(amount, penalty)?)
+    }
+
+    /// Moves `penalty` out of the contract to the treasury, or burns it when there is none.
+    fn _charge_penalty(&mut self, pool_id: PoolId, account: AccountId, penalty: Balance) -> Result<(), StakingError> {
+        if penalty == 0 {
+            return Ok(());
+        }
+
+        let token = self._pool(pool_id)?.token;
+        match self.treasury_impl() {
+            Some(treasury) => PSP22Ref::transfer(&token, treasury, penalty, Vec::default())?,
+            None => PSP22BurnableRef::burn(&token, Self::env().account_id(), penalty)?,
+        }
+        self._emit_penalty_charged_event(pool_id, account, penalty);
+
+        Ok(())
+    }
+
+    fn get_stake_impl(&self, pool_id: PoolId, account: AccountId) -> Option<StakeInfo> {
+        self.get_position_impl(pool_id, account, DEFAULT_POSITION)
+    }
+
+    fn get_position_impl(&self, pool_id: PoolId, account: AccountId, position_id: PositionId) -> Option<StakeInfo> {
+        let StakeRecord::V1(staker) = self.data().stake_records.get(&(pool_id, account, position_id))?;
+        Some(staker)
+    }
+
+    /// Moves the first release's stakes of `accounts` into `DEFAULT_POOL`, which is created
+    /// from the first release's token if the contract has no pools yet, and returns how many
+    /// were moved. `STORAGE_VERSION` is recorded with the batch marked `complete` only.
+    fn migrate_impl(&mut self, accounts: Vec<AccountId>, complete: bool) -> Result<u32, StakingError> {
+        if self.pool_count_impl() == 0 {
+            let token = self.data().token.get().ok_or(StakingError::TokenNotSet)?;
+            self.data().reward_token.set(&token);
+            self.create_pool_impl(token, LEGACY_REWARD_RATE, 1)?;
+        }
+
+        let mut migrated = 0;
+        for account in accounts {
+            if self._migrate_legacy_stake(account)? {
+                migrated += 1;
+            }
+        }
+
+        if complete {
+            self.data().storage_version.set(&STORAGE_VERSION);
+        }
+        self._emit_migrated_event(self.storage_version_impl(), migrated);
+        Ok(migrated)
+    }
+
+    /// Moves `account`'s first-release stake into its `DEFAULT_POSITION`, or into a new
+    /// position if the account staked with the running code before being migrated.
+    fn _migrate_legacy_stake(&mut self, account: AccountId) -> Result<bool, StakingError> {
+        let Some(legacy) = self.data().stakes.get(&account) else {
+            return Ok(false);
+        };
+
+        let pool = self._update_pool(DEFAULT_POOL)?;
+        let position_id = match self.get_position_impl(DEFAULT_POOL, account, DEFAULT_POSITION) {
+            Some(_) => self._next_position_id(DEFAULT_POOL, account)?,
+            None => DEFAULT_POSITION,
+        };
+        let staker = StakeInfo {
+            amount: legacy.amount,
+            // the fixed rate keeps accruing from the original stake time
+            timestamp: legacy.timestamp,
+            // the first release only paid the fixed rate, so the stake joins the emission
+            // accumulator at its current value instead of claiming past emissions
+            reward_debt: pool.reward_per_token_stored,
+            unlock_at: 0,
+            pending_rewards: 0,
+            boost: HUNDRED_PERCENT,
+        };
+        self.data().stakes.remove(&account);
+        self._update_stake(DEFAULT_POOL, account, position_id, Some(staker))?;
+
+        Ok(true)
+    }
+
+    fn storage_version_impl(&self) -> u32 {
+        self.data().storage_version.get().unwrap_or_default()
+    }
+
+    fn positions_of_impl(&self, pool_id: PoolId, account: AccountId) -> Vec<(PositionId, StakeInfo)> {
+        self.data()
+            .positions
+            .get(&(pool_id, account))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|position_id| {
+                self.get_position_impl(pool_id, account, position_id)
+                    .map(|staker| (position_id, staker))
+            })
+            .collect()
+    }
+
+    fn unbonding_of_impl(&self, account: AccountId) -> Vec<UnbondingEntry> {
+        self.data().unbonding.get(&account).unwrap_or_default()
+    }
+
+    /// Positions whose rewards cannot be computed are left out; claiming reports the error.
+    fn pending_rewards_impl(&self, pool_id: PoolId, account: AccountId) -> Balance {
+        let Some(pool) = self.data().pools.get(&pool_id) else {
+            return 0;
+        };
+        let mode = self.reward_mode_impl(pool_id, account);
+        let unpaid = self.data().unpaid_rewards.get(&(pool_id, account)).unwrap_or_default();
+        self.positions_of_impl(pool_id, account)
+            .iter()
+            .filter_map(|(_, staker)| self.earned(pool_id, &pool, staker, mode).ok())
+            .fold(unpaid, Balance::saturating_add)
+    }
+
+    fn pool_impl(&self, pool_id: PoolId) -> Option<Pool> {
+        self.data().pools.get(&pool_id)
+    }
+
+    fn pool_count_impl(&self) -> PoolId {
+        self.data().pool_count.get().unwrap_or_default()
+    }
+
+    fn total_allocation_weight(&self) -> u32 {
+        self.data().total_allocation_weight.get().unwrap_or_default()
+    }
+
+    fn total_staked_impl(&self, pool_id: PoolId) -> Balance {
+        self.data().pools.get(&pool_id).map(|pool| pool.total_staked).unwrap_or_default()
+    }
+
+    fn staking_token_impl(&self, pool_id: PoolId) -> Option<AccountId> {
+        self.data().pools.get(&pool_id).map(|pool| pool.token)
+    }
+
+    fn reward_token_impl(&self) -> Option<AccountId> {
+        self.data().reward_token.get()
+    }
+
+    fn reward_rate_impl(&self, pool_id: PoolId) -> Balance {
+        self.data().pools.get(&pool_id).map(|pool| pool.reward_rate).unwrap_or_default()
+    }
+
+    /// Takes effect from now on: time already accrued keeps earning at the old rate.
+    fn set_reward_rate_impl(&mut self, pool_id: PoolId, reward_rate: Balance) -> Result<(), StakingError> {
+        let mut pool = self._pool(pool_id)?;
+        let now = self.block_timestamp();
+        let cumulative_rate = self._cumulative_rate(pool_id, &pool, now)?;
+        let mut count = self.data().rate_checkpoint_count.get(&pool_id).unwrap_or_default();
+        if count == 0 {
+            // the rate the pool was created with applied from the start
+            let created = RateCheckpoint {
+                timestamp: 0,
+                reward_rate: pool.reward_rate,
+                cumulative_rate: 0,
+            };
+            self.data().rate_checkpoints.insert(&(pool_id, 0), &created);
+            count = 1;
+        }
+        let last = count.checked_sub(1).and_then(|index| {
+            self.data()
+                .rate_checkpoints
+                .get(&(pool_id, index))
+                .map(|checkpoint| (index, checkpoint.timestamp))
+        });
+        let (index, count) = self._next_checkpoint_index(count, last)?;
+        let checkpoint = RateCheckpoint {
+            timestamp: now,
+            reward_rate,
+            cumulative_rate,
+        };
+        self.data().rate_checkpoints.insert(&(pool_id, index), &checkpoint);
+        self.data().rate_checkpoint_count.insert(&pool_id, &count);
+
+        pool.reward_rate = reward_rate;
+        self.data().pools.insert(&pool_id, &pool);
+        self._emit_parameters_changed_event(Self::env().caller(), StakingParameter::RewardRate { pool_id, reward_rate });
+        Ok(())
+    }
+
+    fn set_allocation_weight_impl(&mut self, pool_id: PoolId, allocation_weight: u32) -> Result<(), StakingError> {
+        self._update_all_pools()?;
+
+        let mut pool = self._pool(pool_id)?;
+        let total_allocation_weight = self
+            .total_allocation_weight()
+            .checked_sub(pool.allocation_weight)
+            .ok_or(MathError::Underflow)?
+            .checked_add(allocation_weight)
+            .ok_or(MathError::Overflow)?;
+        pool.allocation_weight = allocation_weight;
+        self.data().pools.insert(&pool_id, &pool);
+        self.data().total_allocation_weight.set(&total_allocation_weight);
+        self._emit_parameters_changed_event(
+            Self::env().caller(),
+            StakingParameter::AllocationWeight {
+                pool_id,
+                allocation_weight,
+            },
+        );
+        Ok(())
+    }
+
+    fn reward_model_impl(&self) -> RewardModel {
+        self.data().reward_model.get().unwrap_or_default()
+    }
+
+    fn emission_rate_impl(&self) -> Balance {
+        self.data().emission_rate.get().unwrap_or_default()
+    }
+
+    fn set_emission_rate_impl(&mut self, emission_rate: Balance) -> Result<(), StakingError> {
+        self._update_all_pools()?;
+        self.data().emission_rate.set(&emission_rate);
+        self._emit_parameters_changed_event(Self::env().caller(), StakingParameter::EmissionRate(emission_rate));
+        Ok(())
+    }
+
+    fn early_unstake_penalty_impl(&self) -> u128 {
+        self.data().early_unstake_penalty.get().unwrap_or_default()
+    }
+
+    fn set_early_unstake_penalty_impl(&mut self, penalty: u128) -> Result<(), StakingError> {
+        if penalty > HUNDRED_PERCENT {
+            return Err(StakingError::InvalidParameter);
+        }
+        self.data().early_unstake_penalty.set(&penalty);
+        self._emit_parameters_changed_event(Self::env().caller(), StakingParameter::EarlyUnstakePenalty(penalty));
+        Ok(())
+    }
+
+    fn compound_bounty_impl(&self) -> u128 {
+        self.data().compound_bounty.get().unwrap_or_default()
+    }
+
+    fn set_compound_bounty_impl(&mut self, bounty: u128) -> Result<(), StakingError> {
+        if bounty > MAX_COMPOUND_BOUNTY {
+            return Err(StakingError::InvalidParameter);
+        }
+        self.data().compound_bounty.set(&bounty);
+        self._emit_parameters_changed_event(Self::env().caller(), StakingParameter::CompoundBounty(bounty));
+        Ok(())
+    }
+
+    fn boost_schedule_impl(&self) -> Vec<BoostTier> {
+        self.data().boost_schedule.get().unwrap_or_default()
+    }
+
+    /// Tiers must be sorted by strictly increasing lock duration, with multipliers between 1x
+    /// and `MAX_BOOST` that never decrease. Only positions staked afterwards are affected.
+    fn set_boost_schedule_impl(&mut self, schedule: Vec<BoostTier>) -> Result<(), StakingError> {
+        if schedule.len() > MAX_BOOST_TIERS {
+            return Err(StakingError::InvalidParameter);
+        }
+        let mut previous = BoostTier { lock_duration: 0, multiplier: HUNDRED_PERCENT };
+        for (index, tier) in schedule.iter().enumerate() {
+            let ordered = index == 0 || tier.lock_duration > previous.lock_duration;
+            if !ordered || tier.multiplier < previous.multiplier || tier.multiplier > MAX_BOOST {
+                return Err(StakingError::InvalidParameter);
+            }
+            previous = *tier;
+        }
+
+        self.data().boost_schedule.set(&schedule);
+        self._emit_parameters_changed_event(Self::env().caller(), StakingParameter::BoostSchedule(schedule));
+        Ok(())
+    }
+
+    /// Multiplier of the longest tier `lock_duration` qualifies for, 1x below the first tier.
+    fn boost_for_impl(&self, lock_duration: Timestamp) -> u128 {
+        self.boost_schedule_impl()
+            .iter()
+            .rev()
+            .find(|tier| tier.lock_duration <= lock_duration)
+            .map(|tier| tier.multiplier)
+            .unwrap_or(HUNDRED_PERCENT)
+    }
+
+    /// Amount the stake earns primary rewards on.
+    fn _boosted(&self, stake_info: &StakeInfo) -> Result<Balance, StakingError> {
+        Ok(math::mul_div(stake_info.amount, stake_info.boost, HUNDRED_PERCENT, Rounding::Down)?)
+    }
+
+    fn boosted_supply_impl(&self, pool_id: PoolId) -> Balance {
+        // every stake predating boosts weighs 1x
+        self.data()
+            .boosted_supply
+            .get(&pool_id)
+            .unwrap_or_else(|| self.total_staked_impl(pool_id))
+    }
+
+    fn treasury_impl(&self) -> Option<AccountId> {
+        self.data().treasury.get().flatten()
+    }
+
+    fn set_treasury_impl(&mut self, treasury: Option<AccountId>) -> Result<(), StakingError> {
+        self.data().treasury.set(&treasury);
+        self._emit_parameters_changed_event(Self::env().caller(), StakingParameter::Treasury(treasury));
+        Ok(())
+    }
+
+    fn unbonding_period_impl(&self) -> Timestamp {
+        self.data().unbonding_period.get().unwrap_or_default()
+    }
+
+    fn set_unbonding_period_impl(&mut self, unbonding_period: Timestamp) -> Result<(), StakingError> {
+        self.data().unbonding_period.set(&unbonding_period);
+        self._emit_parameters_changed_event(Self::env().caller(), StakingParameter::UnbondingPeriod(unbonding_period));
+        Ok(())
+    }
+
+    fn reward_source_impl(&self) -> RewardSource {
+        self.data().reward_source.get().unwrap_or_default()
+    }
+
+    fn set_reward_source_impl(&mut self, reward_source: RewardSource) -> Result<(), StakingError> {
+        self.data().reward_source.set(&reward_source);
+        self._emit_parameters_changed_event(Self::env().caller(), StakingParameter::RewardSource(reward_source));
+        Ok(())
+    }
+
+    fn _emit_pool_created_event(&self, _pool_id: PoolId, _token: AccountId, _reward_rate: Balance, _allocation_weight: u32) {}
+
+    fn _emit_staked_event(&self, _pool_id: PoolId, _account: AccountId, _position_id: PositionId, _amount: Balance) {}
+
+    fn _emit_compounded_event(&self, _pool_id: PoolId, _account: AccountId, _position_id: PositionId, _amount: Balance) {}
+
+    fn _emit_reward_mode_changed_event(&self, _pool_id: PoolId, _account: AccountId, _mode: RewardMode) {}
+
+    fn _emit_delegate_changed_event(&self, _delegator: AccountId, _from_delegatee: Option<AccountId>, _to_delegatee: Option<AccountId>, _rewards: bool) {}
+
+    fn _emit_compound_bounty_paid_event(&self, _pool_id: PoolId, _keeper: AccountId, _amount: Balance) {}
+
+    fn _emit_position_closed_event(&self, _pool_id: PoolId, _account: AccountId, _position_id: PositionId) {}
+
+    fn _emit_boost_expired_event(&self, _pool_id: PoolId, _account: AccountId, _position_id: PositionId) {}
+
+    fn _emit_unstaked_event(&self, _pool_id: PoolId, _account: AccountId, _amount: Balance) {}
+
+    fn _emit_unbonding_started_event(&self, _pool_id: PoolId, _account: AccountId, _amount: Balance, _release_at: Timestamp) {}
+
+    fn _emit_rewards_claimed_event(&self, _pool_id: PoolId, _account: AccountId, _amount: Balance) {}
+
+    fn _emit_rewards_minted_event(&self, _account: AccountId, _amount: Balance) {}
+
+    fn _emit_penalty_charged_event(&self, _pool_id: PoolId, _account: AccountId, _amount: Balance) {}
+
+    fn _emit_rewards_funded_event(&self, _caller: AccountId, _amount: Balance) {}
+
+    fn _emit_extra_reward_funded_event(&self, _pool_id: PoolId, _caller: AccountId, _reward_token: AccountId, _amount: Balance) {}
+
+    fn _emit_extra_rewards_claimed_event(&self, _pool_id: PoolId, _account: AccountId, _reward_token: AccountId, _amount: Balance) {}
+
+    fn _emit_excess_rewards_withdrawn_event(&self, _to: AccountId, _amount: Balance) {}
+
+    fn _emit_parameters_changed_event(&self, _caller: AccountId, _parameter: StakingParameter) {}
+
+    fn _emit_emergency_withdrawn_event(&self, _pool_id: PoolId, _account: AccountId, _amount: Balance) {}
+
+    fn _emit_paused_changed_event(&self, _caller: AccountId, _paused: bool) {}
+
+    fn _emit_migrated_event(&self, _storage_version: u32, _records: u32) {}
+
     fn block_timestamp(&self) -> Timestamp {
-        return Self::env()::block_timestamp();
+        Self::env().block_timestamp()
     }
 }
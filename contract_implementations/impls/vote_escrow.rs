@@ -0,0 +1,256 @@
+use crate::impls::staking::StakingImpl;
+use crate::impls::staking::DEFAULT_POOL;
+use crate::math;
+use crate::math::Rounding;
+use crate::traits::errors::StakingError;
+use ink::prelude::vec::Vec;
+use openbrush::contracts::psp22::*;
+use openbrush::storage::Mapping;
+use openbrush::traits::AccountId;
+use openbrush::traits::Balance;
+use openbrush::traits::DefaultEnv;
+use openbrush::traits::Storage;
+use openbrush::traits::Timestamp;
+
+/// Locks end on week boundaries so that the total voting power only changes slope weekly.
+pub const WEEK: Timestamp = 7 * 86400;
+/// Longest lock; a lock this long gives one vote per locked token.
+pub const MAX_LOCK_DURATION: Timestamp = 4 * 365 * 86400;
+const MAX_LOCK_WEEKS: u64 = MAX_LOCK_DURATION / WEEK + 1;
+
+#[derive(Debug, Default)]
+#[openbrush::storage_item]
+pub struct VoteEscrowData {
+    pub locks: Mapping<AccountId, VoteLock>,
+    /// Sum of all locks' voting power as of `total.timestamp`.
+    #[lazy]
+    pub total: VotePoint,
+    /// Locked amount whose voting power stops decaying at each week boundary.
+    pub slope_changes: Mapping<Timestamp, Balance>,
+}
+
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct VoteLock {
+    /// Tokens of `DEFAULT_POOL`'s staking token held until `unlock_at`.
+    pub amount: Balance,
+    pub unlock_at: Timestamp,
+}
+
+/// Aggregate voting power, kept in locked token-seconds so that decay stays exact:
+/// `bias` drops by `slope` every second and voting power is `bias / MAX_LOCK_DURATION`.
+#[derive(scale::Decode, scale::Encode, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct VotePoint {
+    pub bias: u128,
+    pub slope: Balance,
+    pub timestamp: Timestamp,
+}
+
+/// Vote escrow on top of the staking contract: tokens locked here earn no rewards but give
+/// non-transferable voting power that decays linearly to zero at unlock. Locked tokens count
+/// as principal liabilities, so they are never paid out as rewards.
+pub trait VoteEscrowImpl: Storage<VoteEscrowData> + StakingImpl {
+    /// Locks `amount` of the caller's tokens until `lock_duration` from now, rounded down to
+    /// a week boundary.
+    fn create_lock_impl(&mut self, amount: Balance, lock_duration: Timestamp) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        let caller = Self::env().caller();
+        let lock = self.lock_of_impl(caller);
+        if lock.amount > 0 {
+            return Err(StakingError::LockExists);
+        }
+        if amount == 0 {
+            return Err(StakingError::InvalidParameter);
+        }
+
+        let unlock_at = self._lock_end(lock_duration)?;
+        self._lock(caller, lock, VoteLock { amount, unlock_at }, amount)
+    }
+
+    /// Adds `amount` to the caller's lock, keeping its unlock time.
+    fn increase_lock_amount_impl(&mut self, amount: Balance) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        let caller = Self::env().caller();
+        let lock = self._active_lock(caller)?;
+        if amount == 0 {
+            return Err(StakingError::InvalidParameter);
+        }
+
+        let new_lock = VoteLock {
+            amount: math::add(lock.amount, amount)?,
+            unlock_at: lock.unlock_at,
+        };
+        self._lock(caller, lock, new_lock, amount)
+    }
+
+    /// Moves the caller's unlock time to `lock_duration` from now, which must be later than
+    /// the current one.
+    fn extend_lock_impl(&mut self, lock_duration: Timestamp) -> Result<(), StakingError> {
+        self._ensure_not_paused()?;
+        let caller = Self::env().caller();
+        let lock = self._active_lock(caller)?;
+        let unlock_at = self._lock_end(lock_duration)?;
+        if unlock_at <= lock.unlock_at {
+            return Err(StakingError::InvalidParameter);
+        }
+
+        let new_lock = VoteLock { amount: lock.amount, unlock_at };
+        self._lock(caller, lock, new_lock, 0)
+    }
+
+    /// Returns the caller's tokens once the lock has expired. Works while paused.
+    fn withdraw_lock_impl(&mut self) -> Result<(), StakingError> {
+        let caller = Self::env().caller();
+        let lock = self.lock_of_impl(caller);
+        if lock.amount == 0 {
+            return Err(StakingError::NoLock);
+        }
+        if lock.unlock_at > self.block_timestamp() {
+            return Err(StakingError::StillLocked);
+        }
+
+        // an expired lock no longer counts towards the total
+        self._checkpoint_total()?;
+        self.data::<VoteEscrowData>().locks.remove(&caller);
+
+        let token = self._escrow_token()?;
+        self._sub_liability(token, lock.amount)?;
+        self._transfer_principal(token, caller, lock.amount)?;
+        self._emit_lock_withdrawn_event(caller, lock.amount);
+
+        Ok(())
+    }
+
+    fn lock_of_impl(&self, account: AccountId) -> VoteLock {
+        self.data::<VoteEscrowData>().locks.get(&account).unwrap_or_default()
+    }
+
+    fn voting_power_impl(&self, account: AccountId) -> Balance {
+        let lock = self.lock_of_impl(account);
+        let now = self.block_timestamp();
+        if lock.unlock_at <= now {
+            return 0;
+        }
+        math::mul_div(
+            lock.amount,
+            (lock.unlock_at - now) as u128,
+            MAX_LOCK_DURATION as u128,
+            Rounding::Down,
+        )
+        .unwrap_or_default()
+    }
+
+    fn total_voting_power_impl(&self) -> Balance {
+        let total = self.data::<VoteEscrowData>().total.get().unwrap_or_default();
+        self._advance(total, self.block_timestamp())
+            .map(|point| point.bias / MAX_LOCK_DURATION as u128)
+            .unwrap_or_default()
+    }
+
+    fn _active_lock(&self, account: AccountId) -> Result<VoteLock, StakingError> {
+        let lock = self.lock_of_impl(account);
+        if lock.amount == 0 {
+            return Err(StakingError::NoLock);
+        }
+        if lock.unlock_at <= self.block_timestamp() {
+            return Err(StakingError::LockExpired);
+        }
+        Ok(lock)
+    }
+
+    fn _lock_end(&self, lock_duration: Timestamp) -> Result<Timestamp, StakingError> {
+        let now = self.block_timestamp();
+        if lock_duration > MAX_LOCK_DURATION {
+            return Err(StakingError::InvalidParameter);
+        }
+        let unlock_at = math::add_duration(now, lock_duration)? / WEEK * WEEK;
+        if unlock_at <= now {
+            return Err(StakingError::InvalidParameter);
+        }
+        Ok(unlock_at)
+    }
+
+    fn _escrow_token(&self) -> Result<AccountId, StakingError> {
+        self.staking_token_impl(DEFAULT_POOL).ok_or(StakingError::PoolNotFound)
+    }
+
+    /// Replaces `account`'s lock, pulling in `deposit` more tokens, and moves the lock's
+    /// contribution to the total voting power from `old` to `new`.
+    fn _lock(&mut self, account: AccountId, old: VoteLock, new: VoteLock, deposit: Balance) -> Result<(), StakingError> {
+        let now = self.block_timestamp();
+        let mut total = self._checkpoint_total()?;
+
+        if old.unlock_at > now {
+            total.bias = math::sub(total.bias, math::mul(old.amount, (old.unlock_at - now) as u128)?)?;
+            total.slope = math::sub(total.slope, old.amount)?;
+            let slope_change = self._slope_change(old.unlock_at);
+            self._set_slope_change(old.unlock_at, math::sub(slope_change, old.amount)?);
+        }
+        total.bias = math::add(total.bias, math::mul(new.amount, (new.unlock_at - now) as u128)?)?;
+        total.slope = math::add(total.slope, new.amount)?;
+        let slope_change = self._slope_change(new.unlock_at);
+        self._set_slope_change(new.unlock_at, math::add(slope_change, new.amount)?);
+
+        self.data::<VoteEscrowData>().total.set(&total);
+        self.data::<VoteEscrowData>().locks.insert(&account, &new);
+
+        if deposit > 0 {
+            let token = self._escrow_token()?;
+            self._add_liability(token, deposit)?;
+            PSP22Ref::transfer_from(&token, account, Self::env().account_id(), deposit, Vec::default())?;
+        }
+        self._emit_lock_changed_event(account, new.amount, new.unlock_at);
+
+        Ok(())
+    }
+
+    /// Brings the stored total up to the current time.
+    fn _checkpoint_total(&mut self) -> Result<VotePoint, StakingError> {
+        let total = self.data::<VoteEscrowData>().total.get().unwrap_or_default();
+        let total = self._advance(total, self.block_timestamp())?;
+        self.data::<VoteEscrowData>().total.set(&total);
+        Ok(total)
+    }
+
+    /// Decays `point` until `to`, dropping the slope of locks as they expire on the way.
+    fn _advance(&self, mut point: VotePoint, to: Timestamp) -> Result<VotePoint, StakingError> {
+        // every lock alive at `point` expires within `MAX_LOCK_WEEKS`
+        for _ in 0..MAX_LOCK_WEEKS {
+            if point.slope == 0 || point.timestamp >= to {
+                break;
+            }
+            let next = math::add_duration(point.timestamp / WEEK * WEEK, WEEK)?.min(to);
+            let elapsed = math::elapsed(point.timestamp, next)?;
+            point.bias = math::sub(point.bias, math::mul(point.slope, elapsed)?)?;
+            if next % WEEK == 0 {
+                point.slope = math::sub(point.slope, self._slope_change(next))?;
+            }
+            point.timestamp = next;
+        }
+        point.timestamp = point.timestamp.max(to);
+        Ok(point)
+    }
+
+    fn _slope_change(&self, at: Timestamp) -> Balance {
+        self.data::<VoteEscrowData>().slope_changes.get(&at).unwrap_or_default()
+    }
+
+    fn _set_slope_change(&mut self, at: Timestamp, slope_change: Balance) {
+        if slope_change == 0 {
+            self.data::<VoteEscrowData>().slope_changes.remove(&at);
+        } else {
+            self.data::<VoteEscrowData>().slope_changes.insert(&at, &slope_change);
+        }
+    }
+
+    fn _emit_lock_changed_event(&self, _account: AccountId, _amount: Balance, _unlock_at: Timestamp) {}
+
+    fn _emit_lock_withdrawn_event(&self, _account: AccountId, _amount: Balance) {}
+}
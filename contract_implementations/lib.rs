@@ -1,6 +1,7 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
 pub mod impls;
+pub mod math;
 pub mod traits;
 
 pub use impls::*;
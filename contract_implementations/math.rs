@@ -0,0 +1,102 @@
+//! Checked fixed-point arithmetic for the reward accounting.
+//!
+//! Contracts are built with `overflow-checks = false`, so plain operators wrap silently in
+//! release builds. Balances, accumulators and clocks go through these helpers instead,
+//! which fail with a `MathError` and state their rounding explicitly.
+
+use crate::traits::errors::MathError;
+use openbrush::traits::Timestamp;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero; used for amounts paid out so that the contract never owes more than it holds.
+    Down,
+    /// Away from zero; used for amounts charged.
+    Up,
+}
+
+pub fn add(a: u128, b: u128) -> Result<u128, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+pub fn sub(a: u128, b: u128) -> Result<u128, MathError> {
+    a.checked_sub(b).ok_or(MathError::Underflow)
+}
+
+pub fn mul(a: u128, b: u128) -> Result<u128, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// `a * b / denominator` with the given rounding.
+pub fn mul_div(a: u128, b: u128, denominator: u128, rounding: Rounding) -> Result<u128, MathError> {
+    if denominator == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+
+    let product = mul(a, b)?;
+    let quotient = product / denominator;
+    if rounding == Rounding::Up && product % denominator != 0 {
+        return add(quotient, 1);
+    }
+    Ok(quotient)
+}
+
+/// `base` raised to `exponent`, where `one` is the fixed-point unit of `base` and of the result.
+/// Every intermediate product is rounded down.
+pub fn pow(base: u128, exponent: u64, one: u128) -> Result<u128, MathError> {
+    let mut result = one;
+    let mut base = base;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_div(result, base, one, Rounding::Down)?;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = mul_div(base, base, one, Rounding::Down)?;
+        }
+    }
+    Ok(result)
+}
+
+/// Time passed from `from` to `to`; fails instead of wrapping when the clock is behind `from`.
+pub fn elapsed(from: Timestamp, to: Timestamp) -> Result<u128, MathError> {
+    to.checked_sub(from).map(u128::from).ok_or(MathError::Underflow)
+}
+
+/// `timestamp + duration`, failing instead of wrapping to an earlier time.
+pub fn add_duration(timestamp: Timestamp, duration: Timestamp) -> Result<Timestamp, MathError> {
+    timestamp.checked_add(duration).ok_or(MathError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_rounds_as_requested() {
+        assert_eq!(mul_div(10, 10, 3, Rounding::Down), Ok(33));
+        assert_eq!(mul_div(10, 10, 3, Rounding::Up), Ok(34));
+        assert_eq!(mul_div(10, 9, 3, Rounding::Up), Ok(30));
+    }
+
+    #[test]
+    fn mul_div_fails_instead_of_wrapping() {
+        assert_eq!(mul_div(u128::MAX, 2, 4, Rounding::Down), Err(MathError::Overflow));
+        assert_eq!(mul_div(1, 1, 0, Rounding::Down), Err(MathError::DivisionByZero));
+    }
+
+    #[test]
+    fn pow_compounds_in_fixed_point() {
+        assert_eq!(pow(1100, 0, 1000), Ok(1000));
+        assert_eq!(pow(1100, 1, 1000), Ok(1100));
+        assert_eq!(pow(1100, 2, 1000), Ok(1210));
+        assert_eq!(pow(1100, 3, 1000), Ok(1331));
+    }
+
+    #[test]
+    fn elapsed_rejects_clock_behind_start() {
+        assert_eq!(elapsed(10, 25), Ok(15));
+        assert_eq!(elapsed(25, 10), Err(MathError::Underflow));
+    }
+}
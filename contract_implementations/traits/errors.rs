@@ -1,11 +1,51 @@
+use openbrush::contracts::access_control::AccessControlError;
 use openbrush::contracts::psp22::PSP22Error;
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum StakingError {
     PSP22Error(PSP22Error),
+    AccessControlError(AccessControlError),
     TokenNotSet,
     GreaterAmountRequested,
+    NoStake,
+    StillLocked,
+    InvalidParameter,
+    UnbondingRequired,
+    NothingToWithdraw,
+    TooManyUnbondingEntries,
+    TooManyPositions,
+    InsufficientRewardReserve,
+    TooManyRewardTokens,
+    UnknownRewardToken,
+    PoolNotFound,
+    TooManyPools,
+    SetCodeHashFailed,
+    Paused,
+    MathError(MathError),
+    CompoundingUnavailable,
+    NotCompounding,
+    TooManyAccounts,
+    NoLock,
+    LockExists,
+    LockExpired,
+    NotDelegated,
+    NotPaused,
+    NotBoosted,
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum MathError {
+    Overflow,
+    Underflow,
+    DivisionByZero,
+}
+
+impl From<MathError> for StakingError {
+    fn from(error: MathError) -> StakingError {
+        StakingError::MathError(error)
+    }
 }
 
 impl From<PSP22Error> for StakingError {
@@ -13,3 +53,64 @@ impl From<PSP22Error> for StakingError {
         StakingError::PSP22Error(error)
     }
 }
+
+impl From<AccessControlError> for StakingError {
+    fn from(error: AccessControlError) -> StakingError {
+        StakingError::AccessControlError(error)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum FactoryError {
+    StakingError(StakingError),
+    AccessControlError(AccessControlError),
+    CodeHashNotSet,
+    PoolExists,
+    InstantiationFailed,
+}
+
+impl From<StakingError> for FactoryError {
+    fn from(error: StakingError) -> FactoryError {
+        FactoryError::StakingError(error)
+    }
+}
+
+impl From<AccessControlError> for FactoryError {
+    fn from(error: AccessControlError) -> FactoryError {
+        FactoryError::AccessControlError(error)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum GovernanceError {
+    StakingError(StakingError),
+    AccessControlError(AccessControlError),
+    MathError(MathError),
+    StakingNotSet,
+    ProposalNotFound,
+    BelowProposalThreshold,
+    VotingClosed,
+    AlreadyVoted,
+    NoVotingPower,
+    NotSucceeded,
+}
+
+impl From<StakingError> for GovernanceError {
+    fn from(error: StakingError) -> GovernanceError {
+        GovernanceError::StakingError(error)
+    }
+}
+
+impl From<AccessControlError> for GovernanceError {
+    fn from(error: AccessControlError) -> GovernanceError {
+        GovernanceError::AccessControlError(error)
+    }
+}
+
+impl From<MathError> for GovernanceError {
+    fn from(error: MathError) -> GovernanceError {
+        GovernanceError::MathError(error)
+    }
+}
@@ -0,0 +1,40 @@
+use crate::traits::errors::FactoryError;
+use openbrush::traits::AccountId;
+use openbrush::traits::Balance;
+use openbrush::traits::Hash;
+use ink::prelude::vec::Vec;
+
+#[openbrush::wrapper]
+pub type StakingFactoryRef = dyn StakingFactory;
+
+#[openbrush::trait_definition]
+pub trait StakingFactory {
+    /// Instantiates a staking contract for `token` from the stored code hash and returns
+    /// its address. Only one pool may exist per staking token.
+    #[ink(message)]
+    fn create_pool(&mut self, token: AccountId, reward_token: AccountId, reward_rate: Balance) -> Result<AccountId, FactoryError>;
+
+    /// Sets the code hash used by new pools and by `upgrade_pools`.
+    #[ink(message)]
+    fn set_code_hash(&mut self, code_hash: Hash) -> Result<(), FactoryError>;
+
+    /// Moves up to `limit` pools, starting at index `start` of `pools()`, to the stored
+    /// code hash. Pools already running it are skipped, as are pools refusing the upgrade,
+    /// which are reported in an event. Returns how many were upgraded.
+    #[ink(message)]
+    fn upgrade_pools(&mut self, start: u32, limit: u32) -> Result<u32, FactoryError>;
+
+    #[ink(message)]
+    fn pool_of(&self, token: AccountId) -> Option<AccountId>;
+
+    /// Every deployed pool, in deployment order.
+    #[ink(message)]
+    fn pools(&self) -> Vec<AccountId>;
+
+    #[ink(message)]
+    fn code_hash(&self) -> Option<Hash>;
+
+    /// Code hash `pool` was last deployed or upgraded with.
+    #[ink(message)]
+    fn pool_code_hash(&self, pool: AccountId) -> Option<Hash>;
+}
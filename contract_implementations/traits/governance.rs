@@ -0,0 +1,52 @@
+use crate::impls::governance::Proposal;
+use crate::impls::governance::ProposalAction;
+use crate::impls::governance::ProposalId;
+use crate::impls::governance::ProposalState;
+use crate::traits::errors::GovernanceError;
+use openbrush::traits::AccountId;
+use openbrush::traits::Balance;
+use openbrush::traits::Timestamp;
+
+#[openbrush::wrapper]
+pub type GovernanceRef = dyn Governance;
+
+#[openbrush::trait_definition]
+pub trait Governance {
+    /// Opens a vote on `action`, snapshotting voting weight just before now. The caller
+    /// needs at least `proposal_threshold` voting weight at the snapshot.
+    #[ink(message)]
+    fn propose(&mut self, action: ProposalAction) -> Result<ProposalId, GovernanceError>;
+
+    /// Votes for or against an active proposal with the caller's weight at its snapshot.
+    #[ink(message)]
+    fn vote(&mut self, proposal_id: ProposalId, support: bool) -> Result<(), GovernanceError>;
+
+    /// Applies a succeeded proposal to the staking contract.
+    #[ink(message)]
+    fn execute(&mut self, proposal_id: ProposalId) -> Result<(), GovernanceError>;
+
+    #[ink(message)]
+    fn proposal(&self, proposal_id: ProposalId) -> Option<Proposal>;
+
+    #[ink(message)]
+    fn proposal_count(&self) -> ProposalId;
+
+    #[ink(message)]
+    fn state(&self, proposal_id: ProposalId) -> Option<ProposalState>;
+
+    #[ink(message)]
+    fn has_voted(&self, proposal_id: ProposalId, account: AccountId) -> bool;
+
+    /// Staking contract that provides the voting weight and is governed.
+    #[ink(message)]
+    fn staking(&self) -> Option<AccountId>;
+
+    #[ink(message)]
+    fn voting_period(&self) -> Timestamp;
+
+    #[ink(message)]
+    fn quorum(&self) -> Balance;
+
+    #[ink(message)]
+    fn proposal_threshold(&self) -> Balance;
+}
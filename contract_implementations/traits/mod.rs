@@ -1,5 +1,13 @@
 pub mod errors;
+#[cfg(feature = "factory")]
+pub mod factory;
+#[cfg(feature = "governance")]
+pub mod governance;
 #[cfg(feature = "staking")]
 pub mod staking;
+#[cfg(feature = "staking")]
+pub mod upgradeable;
+#[cfg(feature = "staking")]
+pub mod vote_escrow;
 
 pub use errors::*;
@@ -1,5 +1,25 @@
+use crate::impls::staking::BoostTier;
+use crate::impls::staking::Delegation;
+use crate::impls::staking::ExtraReward;
+use crate::impls::staking::Pool;
+use crate::impls::staking::PoolId;
+use crate::impls::staking::PositionId;
+use crate::impls::staking::RewardMode;
+use crate::impls::staking::RewardModel;
+use crate::impls::staking::RewardSource;
+use crate::impls::staking::StakeInfo;
+use crate::impls::staking::UnbondingEntry;
 use crate::traits::errors::StakingError;
+use openbrush::contracts::access_control::RoleType;
+use openbrush::traits::AccountId;
 use openbrush::traits::Balance;
+use openbrush::traits::Timestamp;
+use ink::prelude::vec::Vec;
+
+/// Allowed to change staking parameters such as the reward rate.
+pub const PARAMETER_MANAGER: RoleType = ink::selector_id!("PARAMETER_MANAGER");
+/// Allowed to halt and resume staking.
+pub const PAUSER: RoleType = ink::selector_id!("PAUSER");
 
 #[openbrush::wrapper]
 pub type StakingRef = dyn Staking;
@@ -7,8 +27,262 @@ pub type StakingRef = dyn Staking;
 #[openbrush::trait_definition]
 pub trait Staking {
     #[ink(message)]
-    fn stake(&mut self, amount: Balance) -> Result<(), StakingError>;
+    fn stake(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError>;
+
+    /// Stakes `amount` and keeps the caller's position locked for at least `lock_duration`.
+    #[ink(message)]
+    fn stake_locked(&mut self, pool_id: PoolId, amount: Balance, lock_duration: Timestamp) -> Result<(), StakingError>;
+
+    /// Withdraws `amount` of principal and pays out the rewards accrued so far. Only
+    /// available while `unbonding_period` is zero; use `request_unstake` otherwise.
+    #[ink(message)]
+    fn unstake(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError>;
+
+    /// Starts the cooldown for `amount` of the caller's principal in the pool.
+    #[ink(message)]
+    fn request_unstake(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError>;
+
+    /// Pays out the caller's unbonding entries whose cooldown has passed, across all pools.
+    #[ink(message)]
+    fn withdraw_unbonded(&mut self) -> Result<(), StakingError>;
+
+    /// Opens an additional position with its own clock and lock, returning its id.
+    #[ink(message)]
+    fn open_position(&mut self, pool_id: PoolId, amount: Balance, lock_duration: Timestamp) -> Result<PositionId, StakingError>;
+
+    #[ink(message)]
+    fn top_up(&mut self, pool_id: PoolId, position_id: PositionId, amount: Balance) -> Result<(), StakingError>;
+
+    /// Withdraws the whole position and its rewards.
+    #[ink(message)]
+    fn close_position(&mut self, pool_id: PoolId, position_id: PositionId) -> Result<(), StakingError>;
+
+    /// Pays out the caller's accrued rewards on every position in the pool and restarts
+    /// their reward clocks, leaving the staked amounts untouched. The pool's extra reward
+    /// tokens are paid out in the same call. Delegated rewards go to the delegatee.
+    /// Under `RewardSource::Reserve` only what the reserve holds is paid; the rest stays owed.
+    #[ink(message)]
+    fn claim_rewards(&mut self, pool_id: PoolId) -> Result<(), StakingError>;
+
+    /// Claims `account`'s rewards in the pool; only for the account it delegated its rewards to.
+    #[ink(message)]
+    fn claim_rewards_for(&mut self, pool_id: PoolId, account: AccountId) -> Result<(), StakingError>;
+
+    /// Delegates the governance weight of the caller's `DEFAULT_POOL` stake to `delegatee`,
+    /// and its rewards across all pools when `rewards` is set. Replaces any earlier delegation.
+    #[ink(message)]
+    fn delegate(&mut self, delegatee: AccountId, rewards: bool) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn undelegate(&mut self) -> Result<(), StakingError>;
+
+    /// Returns the caller's principal in the pool right away, forfeiting all rewards. Only
+    /// available while paused, as it ignores locks, penalties and the unbonding period.
+    #[ink(message)]
+    fn emergency_withdraw(&mut self, pool_id: PoolId) -> Result<(), StakingError>;
+
+    /// Chooses whether the caller's rewards in the pool are paid out or folded into
+    /// principal. Rewards accrued so far are settled under the previous mode.
+    #[ink(message)]
+    fn set_reward_mode(&mut self, pool_id: PoolId, mode: RewardMode) -> Result<(), StakingError>;
+
+    /// Folds the rewards of a compounding `account` into its principal. Callable by anyone.
+    #[ink(message)]
+    fn compound(&mut self, pool_id: PoolId, account: AccountId) -> Result<(), StakingError>;
+
+    /// Compounds the listed accounts that opted into compounding and pays the caller
+    /// `compound_bounty` of the harvested rewards, returning the bounty.
+    #[ink(message)]
+    fn harvest_and_compound(&mut self, pool_id: PoolId, accounts: Vec<AccountId>) -> Result<Balance, StakingError>;
+
+    /// Drops a position whose lock has ended back to a 1x boost. Callable by anyone.
+    #[ink(message)]
+    fn kick(&mut self, pool_id: PoolId, account: AccountId, position_id: PositionId) -> Result<(), StakingError>;
+
+    /// Adds `amount` of `reward_token` to the funding of one of the pool's extra rewards.
+    #[ink(message)]
+    fn fund_extra_reward(&mut self, pool_id: PoolId, reward_token: AccountId, amount: Balance) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn pool(&self, pool_id: PoolId) -> Option<Pool>;
+
+    /// Pools have ids `0..pool_count()`.
+    #[ink(message)]
+    fn pool_count(&self) -> PoolId;
+
+    /// Returns the position used by `stake` and `unstake`.
+    #[ink(message)]
+    fn get_stake(&self, pool_id: PoolId, account: AccountId) -> Option<StakeInfo>;
+
+    #[ink(message)]
+    fn get_position(&self, pool_id: PoolId, account: AccountId, position_id: PositionId) -> Option<StakeInfo>;
+
+    #[ink(message)]
+    fn positions_of(&self, pool_id: PoolId, account: AccountId) -> Vec<(PositionId, StakeInfo)>;
+
+    #[ink(message)]
+    fn unbonding_of(&self, account: AccountId) -> Vec<UnbondingEntry>;
+
+    /// Rewards `account` has accrued across all its positions in the pool, plus those earlier
+    /// payouts left unpaid because the reward reserve ran short.
+    #[ink(message)]
+    fn pending_rewards(&self, pool_id: PoolId, account: AccountId) -> Balance;
+
+    #[ink(message)]
+    fn total_staked(&self, pool_id: PoolId) -> Balance;
+
+    /// Returns `account`'s stake in the pool as of `timestamp`, across all its positions.
+    #[ink(message)]
+    fn stake_of_at(&self, pool_id: PoolId, account: AccountId, timestamp: Timestamp) -> Balance;
+
+    /// Returns the pool's `total_staked` as of `timestamp`.
+    #[ink(message)]
+    fn total_staked_at(&self, pool_id: PoolId, timestamp: Timestamp) -> Balance;
+
+    #[ink(message)]
+    fn delegates(&self, account: AccountId) -> Option<Delegation>;
+
+    /// Returns the governance weight of `account`: its own `DEFAULT_POOL` stake unless
+    /// delegated away, plus the stake delegated to it.
+    #[ink(message)]
+    fn delegated_power(&self, account: AccountId) -> Balance;
+
+    /// Returns `delegated_power` as of `timestamp`.
+    #[ink(message)]
+    fn delegated_power_at(&self, account: AccountId, timestamp: Timestamp) -> Balance;
+
+    /// Returns the time on the clock stake history is recorded by, which the `timestamp`
+    /// of the `_at` queries refers to.
+    #[ink(message)]
+    fn current_timestamp(&self) -> Timestamp;
+
+    #[ink(message)]
+    fn staking_token(&self, pool_id: PoolId) -> Option<AccountId>;
+
+    #[ink(message)]
+    fn reward_token(&self) -> Option<AccountId>;
+
+    #[ink(message)]
+    fn extra_reward_tokens(&self, pool_id: PoolId) -> Vec<AccountId>;
+
+    #[ink(message)]
+    fn extra_reward(&self, pool_id: PoolId, reward_token: AccountId) -> Option<ExtraReward>;
+
+    /// Returns what `account` has accrued in each of the pool's extra reward tokens.
+    #[ink(message)]
+    fn pending_extra_rewards(&self, pool_id: PoolId, account: AccountId) -> Vec<(AccountId, Balance)>;
+
+    /// Returns the pool's daily reward rate in parts of `HUNDRED_PERCENT`.
+    #[ink(message)]
+    fn reward_rate(&self, pool_id: PoolId) -> Balance;
+
+    #[ink(message)]
+    fn reward_mode(&self, pool_id: PoolId, account: AccountId) -> RewardMode;
+
+    #[ink(message)]
+    fn reward_model(&self) -> RewardModel;
+
+    /// Returns the rewards emitted per second across all pools under `RewardModel::RewardPerToken`.
+    #[ink(message)]
+    fn emission_rate(&self) -> Balance;
+
+    /// Returns the share of a locked withdrawal kept as penalty, in parts of `HUNDRED_PERCENT`.
+    #[ink(message)]
+    fn early_unstake_penalty(&self) -> u128;
+
+    /// Returns the boost tiers by ascending lock duration.
+    #[ink(message)]
+    fn boost_schedule(&self) -> Vec<BoostTier>;
+
+    /// Returns the multiplier a new stake locked for `lock_duration` would get, in parts of
+    /// `HUNDRED_PERCENT`.
+    #[ink(message)]
+    fn boost_for(&self, lock_duration: Timestamp) -> u128;
+
+    /// Returns the share of harvested rewards paid to keepers, in parts of `HUNDRED_PERCENT`.
+    #[ink(message)]
+    fn compound_bounty(&self) -> u128;
+
+    #[ink(message)]
+    fn treasury(&self) -> Option<AccountId>;
+
+    #[ink(message)]
+    fn unbonding_period(&self) -> Timestamp;
+
+    #[ink(message)]
+    fn reward_source(&self) -> RewardSource;
+
+    /// Returns the contract balance available for rewards, i.e. not owed to stakers as principal.
+    #[ink(message)]
+    fn reward_reserve(&self) -> Balance;
+
+    /// While paused, staking, unstaking and claiming fail.
+    #[ink(message)]
+    fn paused(&self) -> bool;
+}
+
+#[openbrush::wrapper]
+pub type StakingAdminRef = dyn StakingAdmin;
+
+#[openbrush::trait_definition]
+pub trait StakingAdmin {
+    /// Adds a pool staking `token` and returns its id.
+    #[ink(message)]
+    fn create_pool(&mut self, token: AccountId, reward_rate: Balance, allocation_weight: u32) -> Result<PoolId, StakingError>;
+
+    #[ink(message)]
+    fn set_reward_rate(&mut self, pool_id: PoolId, reward_rate: Balance) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn set_allocation_weight(&mut self, pool_id: PoolId, allocation_weight: u32) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn set_emission_rate(&mut self, emission_rate: Balance) -> Result<(), StakingError>;
+
+    /// Zero rejects withdrawals before unlock instead of penalizing them.
+    #[ink(message)]
+    fn set_early_unstake_penalty(&mut self, penalty: u128) -> Result<(), StakingError>;
+
+    /// Replaces the boost tiers; positions keep the boost they were staked with until they unlock.
+    #[ink(message)]
+    fn set_boost_schedule(&mut self, schedule: Vec<BoostTier>) -> Result<(), StakingError>;
+
+    /// Capped at `MAX_COMPOUND_BOUNTY`.
+    #[ink(message)]
+    fn set_compound_bounty(&mut self, bounty: u128) -> Result<(), StakingError>;
+
+    /// `None` burns early-unstake penalties.
+    #[ink(message)]
+    fn set_treasury(&mut self, treasury: Option<AccountId>) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn set_unbonding_period(&mut self, unbonding_period: Timestamp) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn set_reward_source(&mut self, reward_source: RewardSource) -> Result<(), StakingError>;
+
+    /// Transfers `amount` of the reward token from the caller into the reward reserve.
+    #[ink(message)]
+    fn fund_rewards(&mut self, amount: Balance) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn withdraw_excess_rewards(&mut self, to: AccountId, amount: Balance) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn pause(&mut self) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn unpause(&mut self) -> Result<(), StakingError>;
 
+    /// Registers or reschedules an extra reward token of the pool emitting `emission_rate`
+    /// per second between `start` and `end`.
     #[ink(message)]
-    fn unstake(&mut self, amount: Balance) -> Result<(), StakingError>;
+    fn set_extra_reward(
+        &mut self,
+        pool_id: PoolId,
+        reward_token: AccountId,
+        emission_rate: Balance,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Result<(), StakingError>;
 }
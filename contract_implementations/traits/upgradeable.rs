@@ -0,0 +1,29 @@
+use crate::traits::errors::StakingError;
+use ink::prelude::vec::Vec;
+use openbrush::contracts::access_control::RoleType;
+use openbrush::traits::AccountId;
+use openbrush::traits::Hash;
+
+/// Allowed to replace the contract's code.
+pub const UPGRADER: RoleType = ink::selector_id!("UPGRADER");
+
+#[openbrush::wrapper]
+pub type UpgradeableRef = dyn Upgradeable;
+
+#[openbrush::trait_definition]
+pub trait Upgradeable {
+    /// Replaces the code of the contract with the code uploaded under `code_hash`,
+    /// keeping its storage.
+    #[ink(message)]
+    fn set_code_hash(&mut self, code_hash: Hash) -> Result<(), StakingError>;
+
+    /// Moves the first release's stakes of `accounts` into `DEFAULT_POOL` and returns how
+    /// many were moved. Meant to be called in batches after `set_code_hash`; stakes not
+    /// migrated yet are invisible to the running code. Mark the last batch `complete` to
+    /// record the new storage version.
+    #[ink(message)]
+    fn migrate(&mut self, accounts: Vec<AccountId>, complete: bool) -> Result<u32, StakingError>;
+
+    #[ink(message)]
+    fn storage_version(&self) -> u32;
+}
@@ -0,0 +1,37 @@
+use crate::impls::vote_escrow::VoteLock;
+use crate::traits::errors::StakingError;
+use openbrush::traits::AccountId;
+use openbrush::traits::Balance;
+use openbrush::traits::Timestamp;
+
+#[openbrush::wrapper]
+pub type VoteEscrowRef = dyn VoteEscrow;
+
+/// Governance locks of `DEFAULT_POOL`'s staking token. A lock of `MAX_LOCK_DURATION` gives
+/// one vote per token, decaying linearly to zero at unlock.
+#[openbrush::trait_definition]
+pub trait VoteEscrow {
+    /// Locks `amount` until `lock_duration` from now, rounded down to a whole week.
+    #[ink(message)]
+    fn create_lock(&mut self, amount: Balance, lock_duration: Timestamp) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn increase_lock_amount(&mut self, amount: Balance) -> Result<(), StakingError>;
+
+    /// Moves the unlock time to `lock_duration` from now; it may only move later.
+    #[ink(message)]
+    fn extend_lock(&mut self, lock_duration: Timestamp) -> Result<(), StakingError>;
+
+    /// Returns the caller's tokens after the lock expired.
+    #[ink(message)]
+    fn withdraw_lock(&mut self) -> Result<(), StakingError>;
+
+    #[ink(message)]
+    fn lock_of(&self, account: AccountId) -> VoteLock;
+
+    #[ink(message)]
+    fn voting_power(&self, account: AccountId) -> Balance;
+
+    #[ink(message)]
+    fn total_voting_power(&self) -> Balance;
+}
@@ -0,0 +1,294 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[openbrush::contract]
+mod governance {
+    use openbrush::traits::Storage;
+    use staking_app::errors::GovernanceError;
+    use staking_app::impls::governance::*;
+    use staking_app::traits::governance::*;
+
+    #[ink(storage)]
+    #[derive(Storage, Default)]
+    pub struct GovernanceContract {
+        #[storage_field]
+        governance: GovernanceData,
+    }
+
+    #[ink(event)]
+    pub struct ProposalCreated {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        #[ink(topic)]
+        proposer: AccountId,
+        action: ProposalAction,
+        vote_end: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct VoteCast {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        #[ink(topic)]
+        voter: AccountId,
+        support: bool,
+        weight: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ProposalExecuted {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+    }
+
+    impl GovernanceImpl for GovernanceContract {
+        fn _emit_proposal_created_event(&self, proposal_id: ProposalId, proposer: AccountId, action: ProposalAction, vote_end: Timestamp) {
+            self.env().emit_event(ProposalCreated { proposal_id, proposer, action, vote_end });
+        }
+
+        fn _emit_vote_cast_event(&self, proposal_id: ProposalId, voter: AccountId, support: bool, weight: Balance) {
+            self.env().emit_event(VoteCast { proposal_id, voter, support, weight });
+        }
+
+        fn _emit_proposal_executed_event(&self, proposal_id: ProposalId) {
+            self.env().emit_event(ProposalExecuted { proposal_id });
+        }
+    }
+
+    impl Governance for GovernanceContract {
+        #[ink(message)]
+        fn propose(&mut self, action: ProposalAction) -> Result<ProposalId, GovernanceError> {
+            self.propose_impl(action)
+        }
+
+        #[ink(message)]
+        fn vote(&mut self, proposal_id: ProposalId, support: bool) -> Result<(), GovernanceError> {
+            self.vote_impl(proposal_id, support)
+        }
+
+        #[ink(message)]
+        fn execute(&mut self, proposal_id: ProposalId) -> Result<(), GovernanceError> {
+            self.execute_impl(proposal_id)
+        }
+
+        #[ink(message)]
+        fn proposal(&self, proposal_id: ProposalId) -> Option<Proposal> {
+            self.proposal_impl(proposal_id)
+        }
+
+        #[ink(message)]
+        fn proposal_count(&self) -> ProposalId {
+            self.proposal_count_impl()
+        }
+
+        #[ink(message)]
+        fn state(&self, proposal_id: ProposalId) -> Option<ProposalState> {
+            self.state_impl(proposal_id)
+        }
+
+        #[ink(message)]
+        fn has_voted(&self, proposal_id: ProposalId, account: AccountId) -> bool {
+            self.has_voted_impl(proposal_id, account)
+        }
+
+        #[ink(message)]
+        fn staking(&self) -> Option<AccountId> {
+            self.staking_impl()
+        }
+
+        #[ink(message)]
+        fn voting_period(&self) -> Timestamp {
+            self.voting_period_impl()
+        }
+
+        #[ink(message)]
+        fn quorum(&self) -> Balance {
+            self.quorum_impl()
+        }
+
+        #[ink(message)]
+        fn proposal_threshold(&self) -> Balance {
+            self.proposal_threshold_impl()
+        }
+    }
+
+    impl GovernanceContract {
+        /// Governs `staking`, which must grant this contract its `PARAMETER_MANAGER` and
+        /// `PAUSER` roles for proposals to be executable.
+        #[ink(constructor)]
+        pub fn new(staking: AccountId, voting_period: Timestamp, quorum: Balance, proposal_threshold: Balance) -> Self {
+            let mut contract = Self::default();
+            contract.governance.staking.set(&staking);
+            contract.governance.voting_period.set(&voting_period);
+            contract.governance.quorum.set(&quorum);
+            contract.governance.proposal_threshold.set(&proposal_threshold);
+            contract
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::build_message;
+        use openbrush::contracts::psp22::psp22_external::PSP22;
+        use psp22_token::StakingTokenRef;
+        use staking_app::impls::staking::DEFAULT_POOL;
+        use staking_app::traits::governance::governance_external::Governance;
+        use staking_app::traits::staking::staking_external::Staking;
+        use staking_app::traits::staking::PARAMETER_MANAGER;
+        use staking_app::traits::staking::PAUSER;
+        use staking_contract::StakingContractRef;
+        use openbrush::contracts::access_control::accesscontrol_external::AccessControl;
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        const REWARD_RATE: Balance = 1000000; // 0.1%
+        /// Voting runs on block time, so the tests wait it out.
+        const VOTING_PERIOD: Timestamp = 10000;
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml ../staking/Cargo.toml")]
+        async fn approved_proposal_changes_reward_rate(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let governance = GovernanceContractRef::new(staking_account_id, VOTING_PERIOD, 5000, 1000);
+
+            let governance_account_id = client.instantiate("governance", &ink_e2e::alice(), governance, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let grant_role = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.grant_role(PARAMETER_MANAGER, Some(governance_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            // the snapshot is taken just before the staking clock's current time
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(100));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // bob has no stake, so cannot reach the proposal threshold
+            let bob_propose = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.propose(ProposalAction::RewardRate(DEFAULT_POOL, 2 * REWARD_RATE)));
+            assert!(client.call(&ink_e2e::bob(), bob_propose, 0, None).await.is_err());
+
+            let alice_propose = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.propose(ProposalAction::RewardRate(DEFAULT_POOL, 2 * REWARD_RATE)));
+            let proposal_id = client.call(&ink_e2e::alice(), alice_propose, 0, None).await.expect("propose failed").return_value().expect("propose failed");
+
+            let alice_vote = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.vote(proposal_id, true));
+            client.call(&ink_e2e::alice(), alice_vote, 0, None).await.expect("vote failed");
+
+            let alice_vote_again = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.vote(proposal_id, true));
+            assert!(client.call(&ink_e2e::alice(), alice_vote_again, 0, None).await.is_err());
+
+            // stake made after the proposal carries no weight on it
+            let transfer = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, 20000, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer, 0, None).await.expect("transfer failed");
+
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 20000));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 20000));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            let bob_vote = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.vote(proposal_id, false));
+            assert!(client.call(&ink_e2e::bob(), bob_vote, 0, None).await.is_err());
+
+            let has_voted = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.has_voted(proposal_id, alice_account));
+            assert!(client.call_dry_run(&ink_e2e::alice(), &has_voted, 0, None).await.return_value());
+
+            let early_execute = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.execute(proposal_id));
+            assert!(client.call(&ink_e2e::alice(), early_execute, 0, None).await.is_err());
+
+            std::thread::sleep(std::time::Duration::from_millis(VOTING_PERIOD + 1000));
+            // dry runs see the time of the last block, so seal one after the voting period
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(200));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let state = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.state(proposal_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &state, 0, None).await.return_value(), Some(ProposalState::Succeeded));
+
+            // anyone may execute a proposal that passed
+            let bob_execute = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.execute(proposal_id));
+            client.call(&ink_e2e::bob(), bob_execute, 0, None).await.expect("execute failed");
+
+            let reward_rate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.reward_rate(DEFAULT_POOL));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &reward_rate, 0, None).await.return_value(), 2 * REWARD_RATE);
+
+            let execute_again = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.execute(proposal_id));
+            assert!(client.call(&ink_e2e::alice(), execute_again, 0, None).await.is_err());
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml ../staking/Cargo.toml")]
+        async fn defeated_proposal_cannot_pause(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let governance = GovernanceContractRef::new(staking_account_id, VOTING_PERIOD, 5000, 1000);
+
+            let governance_account_id = client.instantiate("governance", &ink_e2e::alice(), governance, 0, None).await.expect("instantiate failed").account_id;
+
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let grant_role = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.grant_role(PAUSER, Some(governance_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let transfer = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, 20000, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer, 0, None).await.expect("transfer failed");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 20000));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 20000));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            // the snapshot is taken just before the staking clock's current time
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(100));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let alice_propose = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.propose(ProposalAction::Pause));
+            let proposal_id = client.call(&ink_e2e::alice(), alice_propose, 0, None).await.expect("propose failed").return_value().expect("propose failed");
+
+            let alice_vote = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.vote(proposal_id, true));
+            client.call(&ink_e2e::alice(), alice_vote, 0, None).await.expect("vote failed");
+
+            let bob_vote = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.vote(proposal_id, false));
+            client.call(&ink_e2e::bob(), bob_vote, 0, None).await.expect("vote failed");
+
+            std::thread::sleep(std::time::Duration::from_millis(VOTING_PERIOD + 1000));
+            // dry runs see the time of the last block, so seal one after the voting period
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(200));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let state = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.state(proposal_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &state, 0, None).await.return_value(), Some(ProposalState::Defeated));
+
+            let execute = build_message::<GovernanceContractRef>(governance_account_id.clone()).call(|contract| contract.execute(proposal_id));
+            assert!(client.call(&ink_e2e::alice(), execute, 0, None).await.is_err());
+
+            let paused = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.paused());
+            assert!(!client.call_dry_run(&ink_e2e::alice(), &paused, 0, None).await.return_value());
+
+            Ok(())
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 pub use self::psp22_token::StakingTokenRef;
 
-#[openbrush::implementation(PSP22, PSP22Mintable, AccessControl)]
+#[openbrush::implementation(PSP22, PSP22Mintable, PSP22Burnable, AccessControl)]
 #[openbrush::contract]
 mod psp22_token {
     // use openbrush::contracts::psp22::*;
@@ -11,7 +11,7 @@ mod psp22_token {
     use openbrush::traits::Storage;
     use openbrush::traits::String;
 
-    const STAKING_CONTRACT: RoleType = ink::selector_id!("STAKING_CONTRACT")
+    const STAKING_CONTRACT: RoleType = ink::selector_id!("STAKING_CONTRACT");
 
     #[ink(storage)]
     #[derive(Storage, Default)]
@@ -28,6 +28,12 @@ mod psp22_token {
 
     }
 
+    #[default_impl(PSP22Burnable)]
+    #[modifiers(only_role(STAKING_CONTRACT))]
+    fn burn() {
+
+    }
+
     #[overrider(psp22::Internal)]
     fn _before_token_transfer(
         &mut self,
@@ -40,7 +46,7 @@ mod psp22_token {
                 "Transfer from zero address not allowed!",
             )));
         }
-        OK(());
+        Ok(())
     }
 
     // impl PSP22 for StakingContract {}
@@ -75,7 +81,7 @@ mod psp22_token {
         pub fn set_code_hash(&mut self, new_code_hash: Hash) -> Result<(), PSP22Error> {
             self.env().set_code_hash(&new_code_hash).map_err(|_| PSP22Error::Custom(String::from("Failed to set the code hassh")))?;
 
-            OK(());
+            Ok(())
         }
     }
 
@@ -89,7 +95,7 @@ mod psp22_token {
             let accounts = accounts();
             let mint_amount = 10_000_000;
 
-            let mut staking_contract = StakingContract::new(mint_amount);
+            let mut staking_contract = StakingToken::new(mint_amount);
 
             let alice_balance = PSP22::balance_of(&staking_contract, accounts.alice);
 
@@ -102,7 +108,7 @@ mod psp22_token {
             let mint_amount = 10_000_000;
             let transfer_amount = 1_000;
 
-            let mut staking_contract = StakingContract::new(mint_amount);
+            let mut staking_contract = StakingToken::new(mint_amount);
             let result = PSP22::transfer(
                 &mut staking_contract,
                 accounts.bob,
@@ -113,7 +119,7 @@ mod psp22_token {
             let alice_balance = PSP22::balance_of(&staking_contract, accounts.alice);
             let bob_balance = PSP22::balance_of(&staking_contract, accounts.bob);
 
-            assert_eq!(result.is_ok());
+            assert!(result.is_ok());
             assert_eq!(alice_balance, mint_amount - transfer_amount);
             assert_eq!(bob_balance, transfer_amount);
         }
@@ -1,322 +1,2482 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+pub use self::staking::StakingContractRef;
+
+#[openbrush::implementation(AccessControl)]
 #[openbrush::contract]
 mod staking {
+    use openbrush::modifiers;
     use openbrush::traits::Storage;
     use staking_app::errors::StakingError;
     use staking_app::impls::staking::*;
+    use staking_app::impls::vote_escrow::*;
     use staking_app::traits::staking::*;
+    use staking_app::traits::upgradeable::*;
+    use staking_app::traits::vote_escrow::*;
+
+    #[ink(storage)]
+    #[derive(Storage, Default)]
+    pub struct StakingContract {
+        #[storage_field]
+        staking: StakingData,
+        #[storage_field]
+        vote_escrow: VoteEscrowData,
+        #[storage_field]
+        access_control: access_control::Data,
+        pub timestamp: Timestamp
+    }
+
+    #[ink(event)]
+    pub struct PoolCreated {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        token: AccountId,
+        reward_rate: Balance,
+        allocation_weight: u32,
+    }
+
+    #[ink(event)]
+    pub struct Staked {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        position_id: PositionId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Compounded {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        position_id: PositionId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RewardModeChanged {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        mode: RewardMode,
+    }
+
+    #[ink(event)]
+    pub struct DelegateChanged {
+        #[ink(topic)]
+        delegator: AccountId,
+        #[ink(topic)]
+        from_delegatee: Option<AccountId>,
+        #[ink(topic)]
+        to_delegatee: Option<AccountId>,
+        rewards: bool,
+    }
+
+    #[ink(event)]
+    pub struct CompoundBountyPaid {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        keeper: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PositionClosed {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        position_id: PositionId,
+    }
+
+    #[ink(event)]
+    pub struct BoostExpired {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        position_id: PositionId,
+    }
+
+    #[ink(event)]
+    pub struct Unstaked {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct UnbondingStarted {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        release_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct RewardsClaimed {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RewardsMinted {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct PenaltyCharged {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RewardsFunded {
+        #[ink(topic)]
+        caller: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ExtraRewardFunded {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        caller: AccountId,
+        #[ink(topic)]
+        reward_token: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ExtraRewardsClaimed {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        reward_token: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ExcessRewardsWithdrawn {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct ParametersChanged {
+        #[ink(topic)]
+        caller: AccountId,
+        parameter: StakingParameter,
+    }
+
+    #[ink(event)]
+    pub struct EmergencyWithdrawn {
+        #[ink(topic)]
+        pool_id: PoolId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Migrated {
+        storage_version: u32,
+        records: u32,
+    }
+
+    #[ink(event)]
+    pub struct LockChanged {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        unlock_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct LockWithdrawn {
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        grantee: Option<AccountId>,
+        #[ink(topic)]
+        grantor: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        sender: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleAdminChanged {
+        #[ink(topic)]
+        role: RoleType,
+        previous_admin_role: RoleType,
+        new_admin_role: RoleType,
+    }
+
+    #[overrider(access_control::Internal)]
+    fn _emit_role_granted(&mut self, role: RoleType, grantee: Option<AccountId>, grantor: Option<AccountId>) {
+        self.env().emit_event(RoleGranted { role, grantee, grantor });
+    }
+
+    #[overrider(access_control::Internal)]
+    fn _emit_role_revoked(&mut self, role: RoleType, account: Option<AccountId>, sender: AccountId) {
+        self.env().emit_event(RoleRevoked { role, account, sender });
+    }
+
+    #[overrider(access_control::Internal)]
+    fn _emit_role_admin_changed(&mut self, role: RoleType, previous_admin_role: RoleType, new_admin_role: RoleType) {
+        self.env().emit_event(RoleAdminChanged { role, previous_admin_role, new_admin_role });
+    }
+
+    impl StakingImpl for StakingContract {
+        fn block_timestamp(&self) -> Timestamp {
+            return self.timestamp;
+        }
+
+        fn _emit_pool_created_event(&self, pool_id: PoolId, token: AccountId, reward_rate: Balance, allocation_weight: u32) {
+            self.env().emit_event(PoolCreated { pool_id, token, reward_rate, allocation_weight });
+        }
+
+        fn _emit_staked_event(&self, pool_id: PoolId, account: AccountId, position_id: PositionId, amount: Balance) {
+            self.env().emit_event(Staked { pool_id, account, position_id, amount });
+        }
+
+        fn _emit_compounded_event(&self, pool_id: PoolId, account: AccountId, position_id: PositionId, amount: Balance) {
+            self.env().emit_event(Compounded { pool_id, account, position_id, amount });
+        }
+
+        fn _emit_reward_mode_changed_event(&self, pool_id: PoolId, account: AccountId, mode: RewardMode) {
+            self.env().emit_event(RewardModeChanged { pool_id, account, mode });
+        }
+
+        fn _emit_delegate_changed_event(&self, delegator: AccountId, from_delegatee: Option<AccountId>, to_delegatee: Option<AccountId>, rewards: bool) {
+            self.env().emit_event(DelegateChanged { delegator, from_delegatee, to_delegatee, rewards });
+        }
+
+        fn _emit_compound_bounty_paid_event(&self, pool_id: PoolId, keeper: AccountId, amount: Balance) {
+            self.env().emit_event(CompoundBountyPaid { pool_id, keeper, amount });
+        }
+
+        fn _emit_position_closed_event(&self, pool_id: PoolId, account: AccountId, position_id: PositionId) {
+            self.env().emit_event(PositionClosed { pool_id, account, position_id });
+        }
+
+        fn _emit_boost_expired_event(&self, pool_id: PoolId, account: AccountId, position_id: PositionId) {
+            self.env().emit_event(BoostExpired { pool_id, account, position_id });
+        }
+
+        fn _emit_unstaked_event(&self, pool_id: PoolId, account: AccountId, amount: Balance) {
+            self.env().emit_event(Unstaked { pool_id, account, amount });
+        }
+
+        fn _emit_unbonding_started_event(&self, pool_id: PoolId, account: AccountId, amount: Balance, release_at: Timestamp) {
+            self.env().emit_event(UnbondingStarted { pool_id, account, amount, release_at });
+        }
+
+        fn _emit_rewards_claimed_event(&self, pool_id: PoolId, account: AccountId, amount: Balance) {
+            self.env().emit_event(RewardsClaimed { pool_id, account, amount });
+        }
+
+        fn _emit_rewards_minted_event(&self, account: AccountId, amount: Balance) {
+            self.env().emit_event(RewardsMinted { account, amount });
+        }
+
+        fn _emit_penalty_charged_event(&self, pool_id: PoolId, account: AccountId, amount: Balance) {
+            self.env().emit_event(PenaltyCharged { pool_id, account, amount });
+        }
+
+        fn _emit_rewards_funded_event(&self, caller: AccountId, amount: Balance) {
+            self.env().emit_event(RewardsFunded { caller, amount });
+        }
+
+        fn _emit_extra_reward_funded_event(&self, pool_id: PoolId, caller: AccountId, reward_token: AccountId, amount: Balance) {
+            self.env().emit_event(ExtraRewardFunded { pool_id, caller, reward_token, amount });
+        }
+
+        fn _emit_extra_rewards_claimed_event(&self, pool_id: PoolId, account: AccountId, reward_token: AccountId, amount: Balance) {
+            self.env().emit_event(ExtraRewardsClaimed { pool_id, account, reward_token, amount });
+        }
+
+        fn _emit_excess_rewards_withdrawn_event(&self, to: AccountId, amount: Balance) {
+            self.env().emit_event(ExcessRewardsWithdrawn { to, amount });
+        }
+
+        fn _emit_parameters_changed_event(&self, caller: AccountId, parameter: StakingParameter) {
+            self.env().emit_event(ParametersChanged { caller, parameter });
+        }
+
+        fn _emit_emergency_withdrawn_event(&self, pool_id: PoolId, account: AccountId, amount: Balance) {
+            self.env().emit_event(EmergencyWithdrawn { pool_id, account, amount });
+        }
+
+        fn _emit_paused_changed_event(&self, caller: AccountId, paused: bool) {
+            if paused {
+                self.env().emit_event(Paused { account: caller });
+            } else {
+                self.env().emit_event(Unpaused { account: caller });
+            }
+        }
+
+        fn _emit_migrated_event(&self, storage_version: u32, records: u32) {
+            self.env().emit_event(Migrated { storage_version, records });
+        }
+    }
+
+    impl VoteEscrowImpl for StakingContract {
+        fn _emit_lock_changed_event(&self, account: AccountId, amount: Balance, unlock_at: Timestamp) {
+            self.env().emit_event(LockChanged { account, amount, unlock_at });
+        }
+
+        fn _emit_lock_withdrawn_event(&self, account: AccountId, amount: Balance) {
+            self.env().emit_event(LockWithdrawn { account, amount });
+        }
+    }
+
+    impl Staking for StakingContract {
+        #[ink(message)]
+        fn stake(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError> {
+            self.stake_impl(pool_id, amount)
+        }
+
+        #[ink(message)]
+        fn stake_locked(&mut self, pool_id: PoolId, amount: Balance, lock_duration: Timestamp) -> Result<(), StakingError> {
+            self.stake_locked_impl(pool_id, amount, lock_duration)
+        }
+
+        #[ink(message)]
+        fn unstake(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError> {
+            self.unstake_impl(pool_id, amount)
+        }
+
+        #[ink(message)]
+        fn request_unstake(&mut self, pool_id: PoolId, amount: Balance) -> Result<(), StakingError> {
+            self.request_unstake_impl(pool_id, amount)
+        }
+
+        #[ink(message)]
+        fn withdraw_unbonded(&mut self) -> Result<(), StakingError> {
+            self.withdraw_unbonded_impl()
+        }
+
+        #[ink(message)]
+        fn open_position(&mut self, pool_id: PoolId, amount: Balance, lock_duration: Timestamp) -> Result<PositionId, StakingError> {
+            self.open_position_impl(pool_id, amount, lock_duration)
+        }
+
+        #[ink(message)]
+        fn top_up(&mut self, pool_id: PoolId, position_id: PositionId, amount: Balance) -> Result<(), StakingError> {
+            self.top_up_impl(pool_id, position_id, amount)
+        }
+
+        #[ink(message)]
+        fn close_position(&mut self, pool_id: PoolId, position_id: PositionId) -> Result<(), StakingError> {
+            self.close_position_impl(pool_id, position_id)
+        }
+
+        #[ink(message)]
+        fn claim_rewards(&mut self, pool_id: PoolId) -> Result<(), StakingError> {
+            self.claim_impl(pool_id)
+        }
+
+        #[ink(message)]
+        fn claim_rewards_for(&mut self, pool_id: PoolId, account: AccountId) -> Result<(), StakingError> {
+            self.claim_for_impl(pool_id, account)
+        }
+
+        #[ink(message)]
+        fn delegate(&mut self, delegatee: AccountId, rewards: bool) -> Result<(), StakingError> {
+            self.delegate_impl(delegatee, rewards)
+        }
+
+        #[ink(message)]
+        fn undelegate(&mut self) -> Result<(), StakingError> {
+            self.undelegate_impl()
+        }
+
+        #[ink(message)]
+        fn emergency_withdraw(&mut self, pool_id: PoolId) -> Result<(), StakingError> {
+            self.emergency_withdraw_impl(pool_id)
+        }
+
+        #[ink(message)]
+        fn set_reward_mode(&mut self, pool_id: PoolId, mode: RewardMode) -> Result<(), StakingError> {
+            self.set_reward_mode_impl(pool_id, mode)
+        }
+
+        #[ink(message)]
+        fn compound(&mut self, pool_id: PoolId, account: AccountId) -> Result<(), StakingError> {
+            self.compound_impl(pool_id, account)
+        }
+
+        #[ink(message)]
+        fn harvest_and_compound(&mut self, pool_id: PoolId, accounts: Vec<AccountId>) -> Result<Balance, StakingError> {
+            self.harvest_and_compound_impl(pool_id, accounts)
+        }
+
+        #[ink(message)]
+        fn kick(&mut self, pool_id: PoolId, account: AccountId, position_id: PositionId) -> Result<(), StakingError> {
+            self.kick_impl(pool_id, account, position_id)
+        }
+
+        #[ink(message)]
+        fn fund_extra_reward(&mut self, pool_id: PoolId, reward_token: AccountId, amount: Balance) -> Result<(), StakingError> {
+            self.fund_extra_reward_impl(pool_id, reward_token, amount)
+        }
+
+        #[ink(message)]
+        fn pool(&self, pool_id: PoolId) -> Option<Pool> {
+            self.pool_impl(pool_id)
+        }
+
+        #[ink(message)]
+        fn pool_count(&self) -> PoolId {
+            self.pool_count_impl()
+        }
+
+        #[ink(message)]
+        fn get_stake(&self, pool_id: PoolId, account: AccountId) -> Option<StakeInfo> {
+            self.get_stake_impl(pool_id, account)
+        }
+
+        #[ink(message)]
+        fn get_position(&self, pool_id: PoolId, account: AccountId, position_id: PositionId) -> Option<StakeInfo> {
+            self.get_position_impl(pool_id, account, position_id)
+        }
+
+        #[ink(message)]
+        fn positions_of(&self, pool_id: PoolId, account: AccountId) -> Vec<(PositionId, StakeInfo)> {
+            self.positions_of_impl(pool_id, account)
+        }
+
+        #[ink(message)]
+        fn unbonding_of(&self, account: AccountId) -> Vec<UnbondingEntry> {
+            self.unbonding_of_impl(account)
+        }
+
+        #[ink(message)]
+        fn pending_rewards(&self, pool_id: PoolId, account: AccountId) -> Balance {
+            self.pending_rewards_impl(pool_id, account)
+        }
+
+        #[ink(message)]
+        fn total_staked(&self, pool_id: PoolId) -> Balance {
+            self.total_staked_impl(pool_id)
+        }
+
+        #[ink(message)]
+        fn stake_of_at(&self, pool_id: PoolId, account: AccountId, timestamp: Timestamp) -> Balance {
+            self.stake_of_at_impl(pool_id, account, timestamp)
+        }
+
+        #[ink(message)]
+        fn total_staked_at(&self, pool_id: PoolId, timestamp: Timestamp) -> Balance {
+            self.total_staked_at_impl(pool_id, timestamp)
+        }
+
+        #[ink(message)]
+        fn delegates(&self, account: AccountId) -> Option<Delegation> {
+            self.delegates_impl(account)
+        }
+
+        #[ink(message)]
+        fn delegated_power(&self, account: AccountId) -> Balance {
+            self.delegated_power_impl(account)
+        }
+
+        #[ink(message)]
+        fn delegated_power_at(&self, account: AccountId, timestamp: Timestamp) -> Balance {
+            self.delegated_power_at_impl(account, timestamp)
+        }
+
+        #[ink(message)]
+        fn current_timestamp(&self) -> Timestamp {
+            self.current_timestamp_impl()
+        }
+
+        #[ink(message)]
+        fn staking_token(&self, pool_id: PoolId) -> Option<AccountId> {
+            self.staking_token_impl(pool_id)
+        }
+
+        #[ink(message)]
+        fn reward_token(&self) -> Option<AccountId> {
+            self.reward_token_impl()
+        }
+
+        #[ink(message)]
+        fn extra_reward_tokens(&self, pool_id: PoolId) -> Vec<AccountId> {
+            self.extra_reward_tokens_impl(pool_id)
+        }
+
+        #[ink(message)]
+        fn extra_reward(&self, pool_id: PoolId, reward_token: AccountId) -> Option<ExtraReward> {
+            self.extra_reward_impl(pool_id, reward_token)
+        }
+
+        #[ink(message)]
+        fn pending_extra_rewards(&self, pool_id: PoolId, account: AccountId) -> Vec<(AccountId, Balance)> {
+            self.pending_extra_rewards_impl(pool_id, account)
+        }
+
+        #[ink(message)]
+        fn reward_rate(&self, pool_id: PoolId) -> Balance {
+            self.reward_rate_impl(pool_id)
+        }
+
+        #[ink(message)]
+        fn reward_mode(&self, pool_id: PoolId, account: AccountId) -> RewardMode {
+            self.reward_mode_impl(pool_id, account)
+        }
+
+        #[ink(message)]
+        fn reward_model(&self) -> RewardModel {
+            self.reward_model_impl()
+        }
+
+        #[ink(message)]
+        fn emission_rate(&self) -> Balance {
+            self.emission_rate_impl()
+        }
+
+        #[ink(message)]
+        fn early_unstake_penalty(&self) -> u128 {
+            self.early_unstake_penalty_impl()
+        }
+
+        #[ink(message)]
+        fn boost_schedule(&self) -> Vec<BoostTier> {
+            self.boost_schedule_impl()
+        }
+
+        #[ink(message)]
+        fn boost_for(&self, lock_duration: Timestamp) -> u128 {
+            self.boost_for_impl(lock_duration)
+        }
+
+        #[ink(message)]
+        fn compound_bounty(&self) -> u128 {
+            self.compound_bounty_impl()
+        }
+
+        #[ink(message)]
+        fn treasury(&self) -> Option<AccountId> {
+            self.treasury_impl()
+        }
+
+        #[ink(message)]
+        fn unbonding_period(&self) -> Timestamp {
+            self.unbonding_period_impl()
+        }
+
+        #[ink(message)]
+        fn reward_source(&self) -> RewardSource {
+            self.reward_source_impl()
+        }
+
+        #[ink(message)]
+        fn reward_reserve(&self) -> Balance {
+            self.reward_reserve_impl()
+        }
+
+        #[ink(message)]
+        fn paused(&self) -> bool {
+            self.paused_impl()
+        }
+    }
+
+    impl StakingAdmin for StakingContract {
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn create_pool(&mut self, token: AccountId, reward_rate: Balance, allocation_weight: u32) -> Result<PoolId, StakingError> {
+            self.create_pool_impl(token, reward_rate, allocation_weight)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PARAMETER_MANAGER))]
+        fn set_reward_rate(&mut self, pool_id: PoolId, reward_rate: Balance) -> Result<(), StakingError> {
+            self.set_reward_rate_impl(pool_id, reward_rate)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PARAMETER_MANAGER))]
+        fn set_allocation_weight(&mut self, pool_id: PoolId, allocation_weight: u32) -> Result<(), StakingError> {
+            self.set_allocation_weight_impl(pool_id, allocation_weight)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PARAMETER_MANAGER))]
+        fn set_emission_rate(&mut self, emission_rate: Balance) -> Result<(), StakingError> {
+            self.set_emission_rate_impl(emission_rate)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PARAMETER_MANAGER))]
+        fn set_early_unstake_penalty(&mut self, penalty: u128) -> Result<(), StakingError> {
+            self.set_early_unstake_penalty_impl(penalty)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PARAMETER_MANAGER))]
+        fn set_boost_schedule(&mut self, schedule: Vec<BoostTier>) -> Result<(), StakingError> {
+            self.set_boost_schedule_impl(schedule)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PARAMETER_MANAGER))]
+        fn set_compound_bounty(&mut self, bounty: u128) -> Result<(), StakingError> {
+            self.set_compound_bounty_impl(bounty)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn set_treasury(&mut self, treasury: Option<AccountId>) -> Result<(), StakingError> {
+            self.set_treasury_impl(treasury)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PARAMETER_MANAGER))]
+        fn set_unbonding_period(&mut self, unbonding_period: Timestamp) -> Result<(), StakingError> {
+            self.set_unbonding_period_impl(unbonding_period)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn set_reward_source(&mut self, reward_source: RewardSource) -> Result<(), StakingError> {
+            self.set_reward_source_impl(reward_source)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn fund_rewards(&mut self, amount: Balance) -> Result<(), StakingError> {
+            self.fund_rewards_impl(amount)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn withdraw_excess_rewards(&mut self, to: AccountId, amount: Balance) -> Result<(), StakingError> {
+            self.withdraw_excess_rewards_impl(to, amount)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PAUSER))]
+        fn pause(&mut self) -> Result<(), StakingError> {
+            self.set_paused_impl(true)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PAUSER))]
+        fn unpause(&mut self) -> Result<(), StakingError> {
+            self.set_paused_impl(false)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PARAMETER_MANAGER))]
+        fn set_extra_reward(
+            &mut self,
+            pool_id: PoolId,
+            reward_token: AccountId,
+            emission_rate: Balance,
+            start: Timestamp,
+            end: Timestamp,
+        ) -> Result<(), StakingError> {
+            self.set_extra_reward_impl(pool_id, reward_token, emission_rate, start, end)
+        }
+    }
+
+    impl Upgradeable for StakingContract {
+        #[ink(message)]
+        #[modifiers(only_role(UPGRADER))]
+        fn set_code_hash(&mut self, code_hash: Hash) -> Result<(), StakingError> {
+            self.env().set_code_hash(&code_hash).map_err(|_| StakingError::SetCodeHashFailed)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn migrate(&mut self, accounts: Vec<AccountId>, complete: bool) -> Result<u32, StakingError> {
+            self.migrate_impl(accounts, complete)
+        }
+
+        #[ink(message)]
+        fn storage_version(&self) -> u32 {
+            self.storage_version_impl()
+        }
+    }
+
+    impl VoteEscrow for StakingContract {
+        #[ink(message)]
+        fn create_lock(&mut self, amount: Balance, lock_duration: Timestamp) -> Result<(), StakingError> {
+            self.create_lock_impl(amount, lock_duration)
+        }
+
+        #[ink(message)]
+        fn increase_lock_amount(&mut self, amount: Balance) -> Result<(), StakingError> {
+            self.increase_lock_amount_impl(amount)
+        }
+
+        #[ink(message)]
+        fn extend_lock(&mut self, lock_duration: Timestamp) -> Result<(), StakingError> {
+            self.extend_lock_impl(lock_duration)
+        }
+
+        #[ink(message)]
+        fn withdraw_lock(&mut self) -> Result<(), StakingError> {
+            self.withdraw_lock_impl()
+        }
+
+        #[ink(message)]
+        fn lock_of(&self, account: AccountId) -> VoteLock {
+            self.lock_of_impl(account)
+        }
+
+        #[ink(message)]
+        fn voting_power(&self, account: AccountId) -> Balance {
+            self.voting_power_impl(account)
+        }
+
+        #[ink(message)]
+        fn total_voting_power(&self) -> Balance {
+            self.total_voting_power_impl()
+        }
+    }
+
+    impl StakingContract {
+        #[ink(constructor)]
+        pub fn new(token: AccountId, reward_token: AccountId, reward_rate: Balance) -> Self {
+            Self::init(token, reward_token, reward_rate)
+        }
+
+        /// Shares `emission_rate` rewards per second across all stakers instead of
+        /// paying a fixed rate on every stake.
+        #[ink(constructor)]
+        pub fn new_with_emission(token: AccountId, reward_token: AccountId, emission_rate: Balance) -> Self {
+            let mut contract = Self::init(token, reward_token, 0);
+            contract.staking.reward_model.set(&RewardModel::RewardPerToken);
+            contract.staking.emission_rate.set(&emission_rate);
+            contract
+        }
+
+        /// Sets up `DEFAULT_POOL` staking `token` with the full allocation weight.
+        fn init(token: AccountId, reward_token: AccountId, reward_rate: Balance) -> Self {
+            let mut contract = Self::default();
+            contract.staking.storage_version.set(&STORAGE_VERSION);
+            contract.staking.reward_token.set(&reward_token);
+            contract
+                .create_pool_impl(token, reward_rate, 1)
+                .expect("the first pool always fits");
+
+            let caller = contract.env().caller();
+            access_control::Internal::_init_with_admin(&mut contract, Some(caller));
+            access_control::Internal::_setup_role(&mut contract, PARAMETER_MANAGER, Some(caller));
+            access_control::Internal::_setup_role(&mut contract, PAUSER, Some(caller));
+            access_control::Internal::_setup_role(&mut contract, UPGRADER, Some(caller));
+            contract
+        }
+
+
+        /// Test clock used in place of the block timestamp. It only moves forward, as reward
+        /// accrual and the stake history governance snapshots read rely on it.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_timestamp(&mut self, timestamp: Timestamp) -> Result<(), StakingError> {
+            if timestamp < self.timestamp {
+                return Err(StakingError::InvalidParameter);
+            }
+            self.timestamp = timestamp;
+            self._emit_parameters_changed_event(self.env().caller(), StakingParameter::Timestamp(timestamp));
+            Ok(())
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::build_message;
+        use openbrush::contracts::psp22::psp22_external::PSP22;
+        use psp22_token::StakingTokenRef;
+        use staking_legacy::StakingLegacyRef;
+        use staking_app::traits::staking::staking_external::Staking;
+        use staking_app::traits::staking::stakingadmin_external::StakingAdmin;
+        use staking_app::traits::upgradeable::upgradeable_external::Upgradeable;
+        use staking_app::traits::vote_escrow::voteescrow_external::VoteEscrow;
+        use openbrush::contracts::access_control::accesscontrol_external::AccessControl;
+        use ink::env::topics::PrefixedValue;
+        use scale::Decode;
+        use scale::Encode;
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        const REWARD_RATE: Balance = 1000000; // 0.1%
+
+        type Event = <StakingContract as ink::reflect::ContractEventBase>::Type;
+
+        /// Topic ink! derives from `value`: its encoding when that fits, its hash otherwise.
+        fn topic<T: scale::Encode>(value: &T) -> [u8; 32] {
+            let encoded = value.encode();
+            let mut topic = [0; 32];
+            if encoded.len() <= topic.len() {
+                topic[..encoded.len()].copy_from_slice(&encoded);
+            } else {
+                ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&encoded, &mut topic);
+            }
+            topic
+        }
+
+        /// Events the staking contract emitted during `result`, decoded and with their topics.
+        fn staking_events<V>(result: &ink_e2e::CallResult<ink_e2e::PolkadotConfig, ink::env::DefaultEnvironment, V>, staking: &AccountId) -> Vec<(Event, Vec<[u8; 32]>)> {
+            result.contract_emitted_events().expect("unreadable events").into_iter().filter(|emitted| &emitted.event.contract == staking).map(|emitted| (Event::decode(&mut &emitted.event.data[..]).expect("undecodable event"), emitted.topics.iter().map(|topic| topic.0).collect())).collect()
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn stake_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let transfer_amount = 10000;
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, transfer_amount, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!");
+
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            let balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await;
+
+            let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await;
+
+            assert_eq!(balance_of_bob_res.return_value(), transfer_amount);
+            assert_eq!(balance_of_alice_res.return_value(), mint_amount - transfer_amount);
+
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, transfer_amount));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, transfer_amount));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            let balance_of_bob_2 = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            let balance_of_bob_res_2 = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob_2, 0, None).await;
+
+            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
+            let balance_of_contract_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_contract, 0, None).await;
+
+            assert_eq!(balance_of_bob_res_2.return_value(), 0);
+            assert_eq!(balance_of_contract_res.return_value(), transfer_amount);
+
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, bob_account));
+            let get_stake_res = client.call_dry_run(&ink_e2e::bob(), &get_stake, 0, None).await;
+
+            let total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(DEFAULT_POOL));
+            let total_staked_res = client.call_dry_run(&ink_e2e::bob(), &total_staked, 0, None).await;
+
+            let staking_token = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.staking_token(DEFAULT_POOL));
+            let staking_token_res = client.call_dry_run(&ink_e2e::bob(), &staking_token, 0, None).await;
+
+            assert_eq!(get_stake_res.return_value().map(|stake| stake.amount), Some(transfer_amount));
+            assert_eq!(total_staked_res.return_value(), transfer_amount);
+            assert_eq!(staking_token_res.return_value(), Some(psp22_account_id));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn unstake_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let transfer_amount = 10000;
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, transfer_amount, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!");
+
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, transfer_amount));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, transfer_amount));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            let unstake_amount = 1000;
+
+            let bob_unstake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, unstake_amount));
+            client.call(&ink_e2e::bob(), bob_unstake, 0, None).await.expect("unstake failed");
+
+            let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await;
+
+            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
+            let balance_of_contract_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_contract, 0, None).await;
+
+            assert_eq!(balance_of_bob_res.return_value(), unstake_amount);
+            assert_eq!(balance_of_contract_res.return_value(), transfer_amount - unstake_amount);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn unstake_more_without_role_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let transfer_amount = 10000;
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, transfer_amount, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!");
+
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, transfer_amount));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, transfer_amount));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 365));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let unstake_amount = 1100;
+
+            let bob_unstake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, unstake_amount));
+            client.call(&ink_e2e::bob(), bob_unstake, 0, None).await.expect("unstake failed");
+
+            let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await;
+
+            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
+            let balance_of_contract_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_contract, 0, None).await;
+
+            // a year at 0.1% a day on the whole stake is minted on top of the principal
+            assert_eq!(balance_of_bob_res.return_value(), unstake_amount + 3650);
+            assert_eq!(balance_of_contract_res.return_value(), transfer_amount - unstake_amount);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn claim_rewards_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let transfer_amount = 10000;
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, transfer_amount, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!");
+
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, transfer_amount));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, transfer_amount));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, bob_account));
+            let pending_rewards_res = client.call_dry_run(&ink_e2e::bob(), &pending_rewards, 0, None).await;
+
+            assert_eq!(pending_rewards_res.return_value(), 100);
+
+            let bob_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            client.call(&ink_e2e::bob(), bob_claim, 0, None).await.expect("claim failed");
+
+            let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await;
+
+            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
+            let balance_of_contract_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_contract, 0, None).await;
+
+            // 10 days at 0.1% per day, minted since the reserve is empty
+            assert_eq!(balance_of_bob_res.return_value(), 100);
+            assert_eq!(balance_of_contract_res.return_value(), transfer_amount);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn top_ups_carry_accrued_rewards(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 30000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            // 10000 staked at day 0, topped up by 10000 at days 10 and 20
+            for day in 0..3 {
+                let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10 * day));
+                client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+                let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+                client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+            }
+
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            let get_stake_res = client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value().expect("stake not found");
+
+            // 100 on 10000 for the first ten days, 200 on 20000 for the next ten
+            assert_eq!(get_stake_res.amount, 30000);
+            assert_eq!(get_stake_res.pending_rewards, 300);
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 30));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await.return_value(), 600);
+
+            let alice_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            client.call(&ink_e2e::alice(), alice_claim, 0, None).await.expect("claim failed");
+
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await.return_value(), mint_amount - 30000 + 600);
+
+            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_contract, 0, None).await.return_value(), 30000);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn compounding_folds_rewards_into_principal(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            // only compounding accounts can be compounded by others
+            let bob_compound = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.compound(DEFAULT_POOL, alice_account));
+            assert!(client.call(&ink_e2e::bob(), bob_compound, 0, None).await.is_err());
+
+            let set_mode = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_mode(DEFAULT_POOL, RewardMode::Compound));
+            client.call(&ink_e2e::alice(), set_mode, 0, None).await.expect("set reward mode failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 100));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // 10000 * 1.001^100, instead of 1000 in simple interest
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await.return_value(), 1051);
+
+            let bob_compound = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.compound(DEFAULT_POOL, alice_account));
+            client.call(&ink_e2e::bob(), bob_compound, 0, None).await.expect("compound failed");
+
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            let get_stake_res = client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value().expect("stake not found");
+            assert_eq!(get_stake_res.amount, 11051);
+            assert_eq!(get_stake_res.pending_rewards, 0);
+
+            // the compounded rewards were minted into the contract as principal
+            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_contract, 0, None).await.return_value(), 11051);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn keepers_earn_a_bounty_for_compounding(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            // 1% of the harvest goes to the keeper
+            let set_bounty = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_compound_bounty(HUNDRED_PERCENT / 100));
+            client.call(&ink_e2e::alice(), set_bounty, 0, None).await.expect("set compound bounty failed");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let set_mode = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_mode(DEFAULT_POOL, RewardMode::Compound));
+            client.call(&ink_e2e::alice(), set_mode, 0, None).await.expect("set reward mode failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 100));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // bob has no stake and is skipped
+            let harvest = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.harvest_and_compound(DEFAULT_POOL, vec![alice_account, bob_account]));
+            let harvest_res = client.call(&ink_e2e::bob(), harvest, 0, None).await.expect("harvest failed");
+            assert_eq!(harvest_res.return_value(), Ok(10));
+
+            // 1051 harvested, 10 of it paid to bob
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            let get_stake_res = client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value().expect("stake not found");
+            assert_eq!(get_stake_res.amount, 11041);
+
+            let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_bob, 0, None).await.return_value(), 10);
+
+            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_contract, 0, None).await.return_value(), 11041);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn partial_unstakes_keep_the_rest_staked(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // pays 4000 of principal plus the 100 accrued on 10000
+            let alice_unstake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, 4000));
+            client.call(&ink_e2e::alice(), alice_unstake, 0, None).await.expect("unstake failed");
+
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value().map(|staker| staker.amount), Some(6000));
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 20));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let alice_unstake_too_much = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, 6001));
+            assert!(client.call(&ink_e2e::alice(), alice_unstake_too_much, 0, None).await.is_err());
+
+            // pays the remaining 6000 plus the 60 accrued on it
+            let alice_unstake_rest = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, 6000));
+            client.call(&ink_e2e::alice(), alice_unstake_rest, 0, None).await.expect("unstake failed");
+
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value(), None);
+
+            let total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(DEFAULT_POOL));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_staked, 0, None).await.return_value(), 0);
+
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await.return_value(), mint_amount + 160);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn emissions_are_split_pro_rata(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let emission_rate = 4;
+            let staking_contract = StakingContractRef::new_with_emission(psp22_account_id, psp22_account_id, emission_rate);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, 10000, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 30000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 30000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(1000));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let alice_pending = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            let alice_pending_res = client.call_dry_run(&ink_e2e::alice(), &alice_pending, 0, None).await;
+
+            let bob_pending = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, bob_account));
+            let bob_pending_res = client.call_dry_run(&ink_e2e::bob(), &bob_pending, 0, None).await;
+
+            // 1000 seconds at 4 per second, split 3:1
+            assert_eq!(alice_pending_res.return_value(), 3000);
+            assert_eq!(bob_pending_res.return_value(), 1000);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn unstake_locked_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let stake_amount = 10000;
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, stake_amount));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake_locked(DEFAULT_POOL, stake_amount, 86400 * 30));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let alice_unstake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, stake_amount));
+            let alice_unstake_res = client.call_dry_run(&ink_e2e::alice(), &alice_unstake, 0, None).await;
+
+            assert_eq!(alice_unstake_res.return_value(), Err(StakingError::StillLocked));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn withdraw_unbonded_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let stake_amount = 10000;
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let set_period = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_unbonding_period(86400 * 7));
+            client.call(&ink_e2e::alice(), set_period, 0, None).await.expect("set unbonding period failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, stake_amount));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, stake_amount));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let alice_request = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.request_unstake(DEFAULT_POOL, stake_amount));
+            client.call(&ink_e2e::alice(), alice_request, 0, None).await.expect("request unstake failed");
+
+            let alice_withdraw = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.withdraw_unbonded());
+            let alice_withdraw_res = client.call_dry_run(&ink_e2e::alice(), &alice_withdraw, 0, None).await;
+
+            assert_eq!(alice_withdraw_res.return_value(), Err(StakingError::NothingToWithdraw));
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 7));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let alice_withdraw = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.withdraw_unbonded());
+            client.call(&ink_e2e::alice(), alice_withdraw, 0, None).await.expect("withdraw failed");
+
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            let balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await;
+
+            let unbonding_of = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unbonding_of(alice_account));
+            let unbonding_of_res = client.call_dry_run(&ink_e2e::alice(), &unbonding_of, 0, None).await;
+
+            assert_eq!(balance_of_alice_res.return_value(), mint_amount);
+            assert!(unbonding_of_res.return_value().is_empty());
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn positions_work(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 20000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let alice_open = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.open_position(DEFAULT_POOL, 10000, 0));
+            let position_id = client.call(&ink_e2e::alice(), alice_open, 0, None).await.expect("open position failed").return_value().expect("open position failed");
+
+            let positions_of = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.positions_of(DEFAULT_POOL, alice_account));
+            let positions_of_res = client.call_dry_run(&ink_e2e::alice(), &positions_of, 0, None).await;
+
+            let positions = positions_of_res.return_value();
+            assert_eq!(positions.len(), 2);
+            assert_eq!(positions[0].0, DEFAULT_POSITION);
+            assert_eq!(positions[1].0, position_id);
+
+            let alice_close = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.close_position(DEFAULT_POOL, position_id));
+            client.call(&ink_e2e::alice(), alice_close, 0, None).await.expect("close position failed");
+
+            let total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(DEFAULT_POOL));
+            let total_staked_res = client.call_dry_run(&ink_e2e::alice(), &total_staked, 0, None).await;
+
+            assert_eq!(total_staked_res.return_value(), 10000);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn lock_boosts_multiply_rewards(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            // 1.5x for 90 days, 2.5x for a year
+            let schedule = vec![
+                BoostTier { lock_duration: 86400 * 90, multiplier: HUNDRED_PERCENT * 3 / 2 },
+                BoostTier { lock_duration: 86400 * 365, multiplier: HUNDRED_PERCENT * 5 / 2 },
+            ];
+            let set_schedule = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_boost_schedule(schedule.clone()));
+            client.call(&ink_e2e::alice(), set_schedule, 0, None).await.expect("set boost schedule failed");
+
+            let boost_for = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.boost_for(86400 * 100));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &boost_for, 0, None).await.return_value(), HUNDRED_PERCENT * 3 / 2);
+
+            let boost_for = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.boost_for(86400 * 30));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &boost_for, 0, None).await.return_value(), HUNDRED_PERCENT);
+
+            // tiers must be ordered by lock duration
+            let reversed = vec![schedule[1], schedule[0]];
+            let set_schedule = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_boost_schedule(reversed.clone()));
+            assert!(client.call(&ink_e2e::alice(), set_schedule, 0, None).await.is_err());
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 30000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let alice_open = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.open_position(DEFAULT_POOL, 10000, 86400 * 90));
+            let position_id = client.call(&ink_e2e::alice(), alice_open, 0, None).await.expect("open position failed").return_value().expect("open position failed");
+
+            let get_position = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_position(DEFAULT_POOL, alice_account, position_id));
+            let get_position_res = client.call_dry_run(&ink_e2e::alice(), &get_position, 0, None).await.return_value().expect("position not found");
+            assert_eq!(get_position_res.boost, HUNDRED_PERCENT * 3 / 2);
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // 100 on the unlocked position, 150 on the boosted one
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await.return_value(), 250);
+
+            // 80 days of lock left is below the first tier, so the topped up position drops to 1x
+            let alice_top_up = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.top_up(DEFAULT_POOL, position_id, 10000));
+            client.call(&ink_e2e::alice(), alice_top_up, 0, None).await.expect("top up failed");
+
+            let get_position = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_position(DEFAULT_POOL, alice_account, position_id));
+            let get_position_res = client.call_dry_run(&ink_e2e::alice(), &get_position, 0, None).await.return_value().expect("position not found");
+            assert_eq!(get_position_res.boost, HUNDRED_PERCENT);
+            assert_eq!(get_position_res.unlock_at, 86400 * 90);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn boost_expires_at_unlock(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let schedule = vec![BoostTier { lock_duration: 86400 * 90, multiplier: HUNDRED_PERCENT * 3 / 2 }];
+            let set_schedule = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_boost_schedule(schedule.clone()));
+            client.call(&ink_e2e::alice(), set_schedule, 0, None).await.expect("set boost schedule failed");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake_locked(DEFAULT_POOL, 10000, 86400 * 90));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 50));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let kick = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.kick(DEFAULT_POOL, alice_account, DEFAULT_POSITION));
+            assert_eq!(client.call_dry_run(&ink_e2e::bob(), &kick, 0, None).await.return_value(), Err(StakingError::StillLocked));
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 100));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // 15 a day for the 90 locked days, then 10 a day
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await.return_value(), 1450);
+
+            // anyone can settle the expired boost
+            let kick = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.kick(DEFAULT_POOL, alice_account, DEFAULT_POSITION));
+            client.call(&ink_e2e::bob(), kick, 0, None).await.expect("kick failed");
+
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            let get_stake_res = client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value().expect("stake not found");
+            assert_eq!(get_stake_res.boost, HUNDRED_PERCENT);
+
+            let kick = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.kick(DEFAULT_POOL, alice_account, DEFAULT_POSITION));
+            assert_eq!(client.call_dry_run(&ink_e2e::bob(), &kick, 0, None).await.return_value(), Err(StakingError::NotBoosted));
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 110));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await.return_value(), 1550);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn reserve_rewards_never_touch_principal(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let set_source = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_source(RewardSource::Reserve));
+            client.call(&ink_e2e::alice(), set_source, 0, None).await.expect("set reward source failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10050));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let alice_fund = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.fund_rewards(50));
+            client.call(&ink_e2e::alice(), alice_fund, 0, None).await.expect("fund rewards failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // 100 accrued but only 50 funded, the rest stays owed
+            let alice_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            client.call(&ink_e2e::alice(), alice_claim, 0, None).await.expect("claim failed");
+
+            let reward_reserve = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.reward_reserve());
+            let reward_reserve_res = client.call_dry_run(&ink_e2e::alice(), &reward_reserve, 0, None).await;
+
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            let pending_rewards_res = client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await;
+
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            let balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await;
+
+            let total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(DEFAULT_POOL));
+            let total_staked_res = client.call_dry_run(&ink_e2e::alice(), &total_staked, 0, None).await;
+
+            assert_eq!(reward_reserve_res.return_value(), 0);
+            assert_eq!(pending_rewards_res.return_value(), 50);
+            assert_eq!(balance_of_alice_res.return_value(), mint_amount - 10000);
+            assert_eq!(total_staked_res.return_value(), 10000);
+
+            // once funded, the owed rewards are paid with the next claim
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 50));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_fund = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.fund_rewards(50));
+            client.call(&ink_e2e::alice(), alice_fund, 0, None).await.expect("fund rewards failed");
+
+            let alice_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            client.call(&ink_e2e::alice(), alice_claim, 0, None).await.expect("claim failed");
+
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            let pending_rewards_res = client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await;
+
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            let balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await;
+
+            assert_eq!(pending_rewards_res.return_value(), 0);
+            assert_eq!(balance_of_alice_res.return_value(), mint_amount - 10000);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn unstake_works_with_empty_reserve(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let set_source = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_source(RewardSource::Reserve));
+            client.call(&ink_e2e::alice(), set_source, 0, None).await.expect("set reward source failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // nothing funded, yet the principal comes back and the 100 accrued stay owed
+            let alice_unstake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_unstake, 0, None).await.expect("unstake failed");
+
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            let balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await;
+
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            let pending_rewards_res = client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await;
+
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            let get_stake_res = client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await;
+
+            assert_eq!(balance_of_alice_res.return_value(), mint_amount);
+            assert_eq!(pending_rewards_res.return_value(), 100);
+            assert_eq!(get_stake_res.return_value(), None);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn rewards_are_paid_in_reward_token(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+            let reward_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+            let reward_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), reward_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, reward_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let stake_amount = 10000;
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, stake_amount, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!");
+
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, stake_amount));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, stake_amount));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            let grant_role = build_message::<StakingTokenRef>(reward_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
-    #[ink(storage)]
-    #[derive(Storage, Default)]
-    pub struct StakingContract {
-        #[storage_field]
-        staking: StakingData,
-        pub timestamp: Timestamp
-    }
+            let bob_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            client.call(&ink_e2e::bob(), bob_claim, 0, None).await.expect("claim failed");
 
-    impl StakingImpl for StakingContract {
-        fn block_timestamp(&self) -> Timestamp {
-            return self.timestamp;
-        }
-    }
+            let staking_balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            let staking_balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &staking_balance_of_bob, 0, None).await;
 
-    impl Staking for StakingContract {
-        #[ink(message)]
-        fn stake(&mut self, amount: Balance) -> Result<(), StakingError> {
-            self.stake_impl(amount)
-        }
+            let reward_balance_of_bob = build_message::<StakingTokenRef>(reward_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            let reward_balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &reward_balance_of_bob, 0, None).await;
 
-        #[ink(message)]
-        fn unstake(&mut self, amount: Balance) -> Result<(), StakingError> {
-            self.unstake_impl(amount)
-        }
-    }
+            assert_eq!(staking_balance_of_bob_res.return_value(), 0);
+            assert_eq!(reward_balance_of_bob_res.return_value(), 100);
 
-    impl StakingContract {
-        #[ink(constructor)]
-        pun fn new(token: AccountId) -> Self {
-            let mut contract = Self::default();
-            contract.staking.token.set(&token);
-            contract
+            Ok(())
         }
 
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn extra_rewards_are_claimed_together(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+            let partner_token = StakingTokenRef::new(mint_amount);
 
-        #[ink(message)]
-        pub fn set_timestamp(&mut self, timestamp: Timestamp) {
-            self.timestamp = timestamp;
-        }
-    }
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+            let partner_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), partner_token, 0, None).await.expect("instantiate failed").account_id;
 
-    #[cfg(all(test, feature = "e2e-tests"))]
-    mod e2e_tests {
-        use super::*;
-        use ink_e2e::build_message;
-        use openbrush::contracts::psp22::psp22_external::PSP22;
-        use psp22_token::StakingTokenRef;
-        use staking_app::traits::staking::staking_external::Staking;
-        use openbrush::contracts::access_control::accesscontrol_external::AccessControl;
-        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, 0);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let set_extra = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_extra_reward(DEFAULT_POOL, partner_account_id, 2, 0, 1000));
+            client.call(&ink_e2e::alice(), set_extra, 0, None).await.expect("set extra reward failed!");
+
+            let partner_approve = build_message::<StakingTokenRef>(partner_account_id.clone()).call(|contract| contract.approve(staking_account_id, 5000));
+            client.call(&ink_e2e::alice(), partner_approve, 0, None).await.expect("approve failed");
+
+            let fund_extra = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.fund_extra_reward(DEFAULT_POOL, partner_account_id, 5000));
+            client.call(&ink_e2e::alice(), fund_extra, 0, None).await.expect("fund extra reward failed");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            // past `end`, so only 1000 seconds of emissions count
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(2000));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let alice_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            client.call(&ink_e2e::alice(), alice_claim, 0, None).await.expect("claim failed");
+
+            let partner_balance_of_alice = build_message::<StakingTokenRef>(partner_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            let partner_balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &partner_balance_of_alice, 0, None).await;
+
+            assert_eq!(partner_balance_of_alice_res.return_value(), mint_amount - 5000 + 2000);
+
+            Ok(())
+        }
 
         #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
-        async fn stake_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        async fn underfunded_extra_reward_stops_at_funding(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
             let mint_amount = 1000000;
             let staking_token = StakingTokenRef::new(mint_amount);
+            let partner_token = StakingTokenRef::new(mint_amount);
 
             let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+            let partner_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), partner_token, 0, None).await.expect("instantiate failed").account_id;
 
-            let staking_contract = StakingContractRef::new(psp22_account_id);
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, 0);
 
             let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
-            
 
-            let transfer_amount = 10000;
             let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
             let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
-            
-            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, transfer_amount, Vec::new()))
-            
 
-            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!")
-            
+            // 2000 scheduled but only 500 funded
+            let set_extra = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_extra_reward(DEFAULT_POOL, partner_account_id, 2, 0, 1000));
+            client.call(&ink_e2e::alice(), set_extra, 0, None).await.expect("set extra reward failed!");
 
-            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
-            let balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await
-            
+            let partner_approve = build_message::<StakingTokenRef>(partner_account_id.clone()).call(|contract| contract.approve(staking_account_id, 500));
+            client.call(&ink_e2e::alice(), partner_approve, 0, None).await.expect("approve failed");
 
+            let fund_extra = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.fund_extra_reward(DEFAULT_POOL, partner_account_id, 500));
+            client.call(&ink_e2e::alice(), fund_extra, 0, None).await.expect("fund extra reward failed");
 
-            let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
-            let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await
-            
+            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, 10000, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!");
 
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
 
-            assert_eq!(balance_of_bob_res.return_value(), transfer_amount);
-            assert_eq!(balance_of_alice_res.return_value(), mint_amount - transfer_amount);
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
 
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
 
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
 
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(2000));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
-            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, transfer_amount));
-            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("Stake failed")
-            
+            // accrual stops once the funding is used up, so each staker is owed half of it
+            let pending_extra = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_extra_rewards(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_extra, 0, None).await.return_value(), vec![(partner_account_id, 250)]);
+
+            let alice_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            client.call(&ink_e2e::alice(), alice_claim, 0, None).await.expect("claim failed");
+
+            // claiming first does not eat into what the other staker is owed
+            let pending_extra = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_extra_rewards(DEFAULT_POOL, bob_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::bob(), &pending_extra, 0, None).await.return_value(), vec![(partner_account_id, 250)]);
+
+            let bob_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            client.call(&ink_e2e::bob(), bob_claim, 0, None).await.expect("claim failed");
 
+            let partner_balance_of_bob = build_message::<StakingTokenRef>(partner_account_id.clone()).call(|contract| contract.balance_of(bob_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::bob(), &partner_balance_of_bob, 0, None).await.return_value(), 250);
 
+            let extra_reward = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.extra_reward(DEFAULT_POOL, partner_account_id));
+            let extra_reward_res = client.call_dry_run(&ink_e2e::alice(), &extra_reward, 0, None).await.return_value().expect("extra reward not found");
+            assert_eq!((extra_reward_res.funded, extra_reward_res.accrued), (0, 0));
 
+            Ok(())
+        }
 
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn pools_track_their_own_stakes(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+            let lp_token = StakingTokenRef::new(mint_amount);
 
-            let bob_stake = build_message::<StakingTokenRef>(staking_account_id.clone()).call(|contract| contract.stake(transfer_amount));
-            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed")
-            
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+            let lp_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), lp_token, 0, None).await.expect("instantiate failed").account_id;
 
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
 
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
 
-            let balance_of_bob_2 = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
-            let balance_of_bob_res_2 = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob_2, 0, None).await;
-            
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
 
+            let bob_create_pool = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.create_pool(lp_account_id, 2 * REWARD_RATE, 1));
+            assert!(client.call(&ink_e2e::bob(), bob_create_pool, 0, None).await.is_err());
 
+            let create_pool = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.create_pool(lp_account_id, 2 * REWARD_RATE, 1));
+            let pool_id = client.call(&ink_e2e::alice(), create_pool, 0, None).await.expect("create pool failed!").return_value().expect("create pool failed!");
 
-            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
-            let balance_of_contract_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_contract, 0, None).await;
-            
+            let lp_approve = build_message::<StakingTokenRef>(lp_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), lp_approve, 0, None).await.expect("approve failed");
 
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(pool_id, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
 
-            assert_eq!(balance_of_bob_res_2.return_value(), 0);
-            assert_eq!(balance_of_contract_res.return_value(), transfer_amount);
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
+            let pool_count = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pool_count());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pool_count, 0, None).await.return_value(), 2);
 
+            let staking_token = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.staking_token(pool_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &staking_token, 0, None).await.return_value(), Some(lp_account_id));
 
-            OK(())
-        }
+            let total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(pool_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_staked, 0, None).await.return_value(), 10000);
+
+            let default_total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(DEFAULT_POOL));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &default_total_staked, 0, None).await.return_value(), 0);
+
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(pool_id, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await.return_value(), 200);
 
+            let unknown_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(pool_id + 1, 10000));
+            assert!(client.call(&ink_e2e::alice(), unknown_stake, 0, None).await.is_err());
 
+            Ok(())
+        }
 
-        
         #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
-        async fn unstake_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        async fn emergency_withdraw_works_while_paused(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
             let mint_amount = 1000000;
             let staking_token = StakingTokenRef::new(mint_amount);
 
             let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
 
-            let staking_contract = StakingContractRef::new(psp22_account_id);
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
 
             let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
-            
 
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
 
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 20000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
 
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake_locked(DEFAULT_POOL, 10000, 86400 * 30));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
 
-            let transfer_amount = 10000;
-            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
-            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
-            
-            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, transfer_amount, Vec::new()))
-            
+            let bob_pause = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pause());
+            assert!(client.call(&ink_e2e::bob(), bob_pause, 0, None).await.is_err());
 
-            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!")
-            
+            let alice_pause = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pause());
+            client.call(&ink_e2e::alice(), alice_pause, 0, None).await.expect("pause failed!");
 
-            // let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
-            // let balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await
-            
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
+            let alice_stake_again = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            assert!(client.call(&ink_e2e::alice(), alice_stake_again, 0, None).await.is_err());
 
-            // let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
-            // let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await
-            
+            let alice_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            assert!(client.call(&ink_e2e::alice(), alice_claim, 0, None).await.is_err());
 
+            let alice_emergency = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.emergency_withdraw(DEFAULT_POOL));
+            client.call(&ink_e2e::alice(), alice_emergency, 0, None).await.expect("emergency withdraw failed");
 
-            // assert_eq!(balance_of_bob_res.return_value(), transfer_amount);
-            // assert_eq!(balance_of_alice_res.return_value(), mint_amount - transfer_amount);
+            // principal back, rewards forfeited
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await.return_value(), mint_amount);
 
+            let total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(DEFAULT_POOL));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_staked, 0, None).await.return_value(), 0);
 
+            let alice_unpause = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unpause());
+            client.call(&ink_e2e::alice(), alice_unpause, 0, None).await.expect("unpause failed!");
 
+            let alice_stake_after = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake_after, 0, None).await.expect("stake failed");
 
-            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, transfer_amount));
-            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("Stake failed")
-            
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn emergency_withdraw_fails_while_unpaused(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
 
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
 
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
 
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
 
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
 
-            let bob_stake = build_message::<StakingTokenRef>(staking_account_id.clone()).call(|contract| contract.stake(transfer_amount));
-            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed")
-            
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake_locked(DEFAULT_POOL, 10000, 86400 * 30));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
 
+            // the lock, penalty and unbonding period cannot be skipped in normal operation
+            let alice_emergency = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.emergency_withdraw(DEFAULT_POOL));
+            let alice_emergency_res = client.call_dry_run(&ink_e2e::alice(), &alice_emergency, 0, None).await;
+            assert_eq!(alice_emergency_res.return_value(), Err(StakingError::NotPaused));
 
-            let unstake_amount = 1000;
+            let total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(DEFAULT_POOL));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_staked, 0, None).await.return_value(), 10000);
 
-            let bob_unstake = build_message::<StakingTokenRef>(staking_account_id.clone()).call(|contract| contract.unstake(unstake_amount));
-            client.call(&ink_e2e::bob(), bob_unstake, 0, None).await.expect("unstake failed");
-            
+            Ok(())
+        }
 
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn emergency_withdraw_keeps_other_stakers_rewards(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
 
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
 
-            let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
-            let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await
-            
+            let emission_rate = 4;
+            let staking_contract = StakingContractRef::new_with_emission(psp22_account_id, psp22_account_id, emission_rate);
 
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
 
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
 
-            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
-            let balance_of_contract_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_contract, 0, None).await;
-            
+            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, 10000, Vec::new()));
+            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!");
 
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 30000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
 
-            assert_eq!(balance_of_bob_res.return_value(), 0);
-            assert_eq!(balance_of_contract_res.return_value(), transfer_amount - unstake_amount);
+            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 30000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let bob_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(1000));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let alice_pause = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pause());
+            client.call(&ink_e2e::alice(), alice_pause, 0, None).await.expect("pause failed!");
+
+            let alice_emergency = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.emergency_withdraw(DEFAULT_POOL));
+            client.call(&ink_e2e::alice(), alice_emergency, 0, None).await.expect("emergency withdraw failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(2000));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let bob_pending = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, bob_account));
+            let bob_pending_res = client.call_dry_run(&ink_e2e::bob(), &bob_pending, 0, None).await;
+
+            // a quarter of the first 1000 seconds, then all of the next 1000
+            assert_eq!(bob_pending_res.return_value(), 1000 + 4000);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn migrate_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let storage_version = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.storage_version());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &storage_version, 0, None).await.return_value(), STORAGE_VERSION);
 
+            let bob_migrate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.migrate(vec![alice_account], true));
+            assert!(client.call(&ink_e2e::bob(), bob_migrate, 0, None).await.is_err());
 
+            // a fresh deployment holds no first-release stakes
+            let alice_migrate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.migrate(vec![alice_account], true));
+            assert_eq!(client.call(&ink_e2e::alice(), alice_migrate, 0, None).await.expect("migrate failed!").return_value(), Ok(0));
 
-            OK(())
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value().map(|staker| staker.amount), Some(10000));
+
+            Ok(())
         }
 
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml ../staking_legacy/Cargo.toml")]
+        async fn migrate_moves_first_release_stakes(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let legacy_contract = StakingLegacyRef::new(psp22_account_id);
+
+            let staking_account_id = client.instantiate("staking_legacy", &ink_e2e::alice(), legacy_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingLegacyRef>(staking_account_id.clone()).call(|contract| contract.stake(10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let staking_code_hash = client.upload("staking_contract", &ink_e2e::alice(), None).await.expect("upload failed").code_hash;
+
+            let alice_upgrade = build_message::<StakingLegacyRef>(staking_account_id.clone()).call(|contract| contract.set_code_hash(staking_code_hash));
+            client.call(&ink_e2e::alice(), alice_upgrade, 0, None).await.expect("set code hash failed!");
+
+            // the first-release record is left untouched until migrated
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value(), None);
+
+            let storage_version = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.storage_version());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &storage_version, 0, None).await.return_value(), 0);
+
+            let alice_migrate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.migrate(vec![alice_account], false));
+            assert_eq!(client.call(&ink_e2e::alice(), alice_migrate, 0, None).await.expect("migrate failed!").return_value(), Ok(1));
 
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            let get_stake_res = client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value().expect("stake not migrated");
+            assert_eq!(get_stake_res.amount, 10000);
+            assert_eq!(get_stake_res.boost, HUNDRED_PERCENT);
 
+            let total_staked = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked(DEFAULT_POOL));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_staked, 0, None).await.return_value(), 10000);
 
+            // more batches may follow, so the version is not recorded yet
+            let storage_version = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.storage_version());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &storage_version, 0, None).await.return_value(), 0);
+
+            let alice_migrate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.migrate(vec![alice_account], true));
+            assert_eq!(client.call(&ink_e2e::alice(), alice_migrate, 0, None).await.expect("migrate failed!").return_value(), Ok(0));
+
+            let storage_version = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.storage_version());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &storage_version, 0, None).await.return_value(), STORAGE_VERSION);
+
+            // the stake keeps earning the first release's rate from when it was made
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await.return_value(), 100);
+
+            Ok(())
+        }
 
-        
         #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
-        async fn unstake_more_without_role_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        async fn voting_power_decays_until_unlock(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
             let mint_amount = 1000000;
             let staking_token = StakingTokenRef::new(mint_amount);
 
             let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
 
-            let staking_contract = StakingContractRef::new(psp22_account_id);
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
 
             let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
-            
 
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 200000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
 
+            let alice_lock = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.create_lock(100000, WEEK * 52));
+            client.call(&ink_e2e::alice(), alice_lock, 0, None).await.expect("create lock failed");
 
+            let voting_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.voting_power(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &voting_power, 0, None).await.return_value(), 100000 * (WEEK * 52) as u128 / MAX_LOCK_DURATION as u128);
+
+            // half way through, half the power is left
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(WEEK * 26));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let total_voting_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_voting_power());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_voting_power, 0, None).await.return_value(), 100000 * (WEEK * 26) as u128 / MAX_LOCK_DURATION as u128);
+
+            let alice_increase = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.increase_lock_amount(100000));
+            client.call(&ink_e2e::alice(), alice_increase, 0, None).await.expect("increase lock amount failed");
+
+            let alice_extend = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.extend_lock(WEEK * 52));
+            client.call(&ink_e2e::alice(), alice_extend, 0, None).await.expect("extend lock failed");
+
+            let voting_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.voting_power(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &voting_power, 0, None).await.return_value(), 200000 * (WEEK * 52) as u128 / MAX_LOCK_DURATION as u128);
+
+            let total_voting_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_voting_power());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_voting_power, 0, None).await.return_value(), 200000 * (WEEK * 52) as u128 / MAX_LOCK_DURATION as u128);
+
+            let alice_withdraw = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.withdraw_lock());
+            assert!(client.call(&ink_e2e::alice(), alice_withdraw, 0, None).await.is_err());
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(WEEK * 78));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let total_voting_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_voting_power());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_voting_power, 0, None).await.return_value(), 0);
+
+            let alice_withdraw = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.withdraw_lock());
+            client.call(&ink_e2e::alice(), alice_withdraw, 0, None).await.expect("withdraw lock failed");
+
+            let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await.return_value(), mint_amount);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn stake_history_is_queryable(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
 
-            let transfer_amount = 10000;
             let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
-            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
-            
-            let transfer_alice_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.transfer(bob_account, transfer_amount, Vec::new()))
-            
 
-            client.call(&ink_e2e::alice(), transfer_alice_bob, 0, None).await.expect("transfer failed!")
-            
+            let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
 
-            // let balance_of_alice = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(alice_account));
-            // let balance_of_alice_res = client.call_dry_run(&ink_e2e::alice(), &balance_of_alice, 0, None).await
-            
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 15000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
 
+            // 10000 staked at 100, 5000 more at 200, 3000 unstaked at 300
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(100));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
-            // let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
-            // let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await
-            
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
 
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(200));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
-            // assert_eq!(balance_of_bob_res.return_value(), transfer_amount);
-            // assert_eq!(balance_of_alice_res.return_value(), mint_amount - transfer_amount);
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 5000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
 
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(300));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
+            let alice_unstake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, 3000));
+            client.call(&ink_e2e::alice(), alice_unstake, 0, None).await.expect("unstake failed");
 
+            for (timestamp, expected) in [(50, 0), (100, 10000), (150, 10000), (250, 15000), (300, 12000), (1000, 12000)] {
+                let stake_of_at = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake_of_at(DEFAULT_POOL, alice_account, timestamp));
+                assert_eq!(client.call_dry_run(&ink_e2e::alice(), &stake_of_at, 0, None).await.return_value(), expected);
 
-            let bob_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, transfer_amount));
-            client.call(&ink_e2e::bob(), bob_approve, 0, None).await.expect("Stake failed")
-            
+                let total_staked_at = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.total_staked_at(DEFAULT_POOL, timestamp));
+                assert_eq!(client.call_dry_run(&ink_e2e::alice(), &total_staked_at, 0, None).await.return_value(), expected);
+            }
 
+            Ok(())
+        }
 
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn delegation_moves_power_and_rewards(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let mint_amount = 1000000;
+            let staking_token = StakingTokenRef::new(mint_amount);
 
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
 
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
 
-            let bob_stake = build_message::<StakingTokenRef>(staking_account_id.clone()).call(|contract| contract.stake(transfer_amount));
-            client.call(&ink_e2e::bob(), bob_stake, 0, None).await.expect("stake failed")
-            
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
 
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
 
             let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
 
-            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!")
-            
-            
-            
-            
-            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 365))
-            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!")
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
 
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
 
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(100));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
+            let alice_delegate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.delegate(bob_account, true));
+            client.call(&ink_e2e::alice(), alice_delegate, 0, None).await.expect("delegate failed");
 
+            let delegates = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.delegates(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &delegates, 0, None).await.return_value(), Some(Delegation { delegatee: bob_account, rewards: true }));
 
-            let unstake_amount = 1100;
+            let bob_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.delegated_power(bob_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &bob_power, 0, None).await.return_value(), 10000);
 
-            let bob_unstake = build_message::<StakingTokenRef>(staking_account_id.clone()).call(|contract| contract.unstake(unstake_amount));
-            client.call(&ink_e2e::bob(), bob_unstake, 0, None).await.expect("unstake failed");
-            
+            let alice_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.delegated_power(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &alice_power, 0, None).await.return_value(), 0);
 
+            // the weight moved at 100 and stays where it was before
+            let alice_power_at = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.delegated_power_at(alice_account, 50));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &alice_power_at, 0, None).await.return_value(), 10000);
 
+            let bob_power_at = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.delegated_power_at(bob_account, 50));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &bob_power_at, 0, None).await.return_value(), 0);
 
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
 
+            let charlie_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards_for(DEFAULT_POOL, alice_account));
+            assert!(client.call(&ink_e2e::charlie(), charlie_claim, 0, None).await.is_err());
 
+            let bob_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards_for(DEFAULT_POOL, alice_account));
+            client.call(&ink_e2e::bob(), bob_claim, 0, None).await.expect("claim failed");
+
+            // alice keeps the principal, bob receives the rewards
             let balance_of_bob = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(bob_account));
-            let balance_of_bob_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_bob, 0, None).await
-            
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &balance_of_bob, 0, None).await.return_value(), 100);
 
+            let get_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.get_stake(DEFAULT_POOL, alice_account));
+            let get_stake_res = client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value().expect("stake not found");
+            assert_eq!(get_stake_res.amount, 10000);
 
+            let alice_undelegate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.undelegate());
+            client.call(&ink_e2e::alice(), alice_undelegate, 0, None).await.expect("undelegate failed");
 
-            let balance_of_contract = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.balance_of(staking_account_id));
-            let balance_of_contract_res = client.call_dry_run(&ink_e2e::bob(), &balance_of_contract, 0, None).await;
-            
+            let bob_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.delegated_power(bob_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &bob_power, 0, None).await.return_value(), 0);
 
+            let alice_power = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.delegated_power(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &alice_power, 0, None).await.return_value(), 10000);
 
-            assert_eq!(balance_of_bob_res.return_value(), unstake_amount);
-            assert_eq!(balance_of_contract_res.return_value(), 0);
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn set_reward_rate_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let new_rate = 2 * REWARD_RATE;
+
+            let bob_set_rate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_rate(DEFAULT_POOL, new_rate));
+            assert!(client.call(&ink_e2e::bob(), bob_set_rate, 0, None).await.is_err());
+
+            let alice_set_rate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_rate(DEFAULT_POOL, new_rate));
+            client.call(&ink_e2e::alice(), alice_set_rate, 0, None).await.expect("set reward rate failed!");
+
+            let reward_rate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.reward_rate(DEFAULT_POOL));
+            let reward_rate_res = client.call_dry_run(&ink_e2e::bob(), &reward_rate, 0, None).await;
+
+            assert_eq!(reward_rate_res.return_value(), new_rate);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn reward_rate_change_is_not_retroactive(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let alice_set_rate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_rate(DEFAULT_POOL, 2 * REWARD_RATE));
+            client.call(&ink_e2e::alice(), alice_set_rate, 0, None).await.expect("set reward rate failed!");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 20));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            // 10 days at 10 a day, then 10 days at 20 a day
+            let pending_rewards = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.pending_rewards(DEFAULT_POOL, alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pending_rewards, 0, None).await.return_value(), 100 + 200);
 
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn set_timestamp_without_role_fails(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
+
+            let bob_set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400));
+            assert!(client.call(&ink_e2e::bob(), bob_set_time, 0, None).await.is_err());
+
+            let bob_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let grant_role = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.grant_role(PARAMETER_MANAGER, Some(bob_account)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let bob_set_rate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_rate(DEFAULT_POOL, 2 * REWARD_RATE));
+            client.call(&ink_e2e::bob(), bob_set_rate, 0, None).await.expect("set reward rate failed!");
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn set_timestamp_only_moves_forward(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
 
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
 
-            OK(())
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 5));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &set_time, 0, None).await.return_value(), Err(StakingError::InvalidParameter));
+
+            let current_timestamp = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.current_timestamp());
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &current_timestamp, 0, None).await.return_value(), 86400 * 10);
+
+            Ok(())
         }
 
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn events_carry_fields_and_topics(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_contract = StakingContractRef::new(psp22_account_id, psp22_account_id, REWARD_RATE);
+
+            let staking_account_id = client.instantiate("staking_contract", &ink_e2e::alice(), staking_contract, 0, None).await.expect("instantiate failed").account_id;
 
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
 
-        
+            let grant_role = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.grant_role(ink::selector_id!("STAKING_CONTRACT"), Some(staking_account_id)));
+            client.call(&ink_e2e::alice(), grant_role, 0, None).await.expect("grant role failed!");
+
+            let alice_approve = build_message::<StakingTokenRef>(psp22_account_id.clone()).call(|contract| contract.approve(staking_account_id, 10000));
+            client.call(&ink_e2e::alice(), alice_approve, 0, None).await.expect("approve failed");
+
+            let alice_stake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.stake(DEFAULT_POOL, 10000));
+            let stake_res = client.call(&ink_e2e::alice(), alice_stake, 0, None).await.expect("stake failed");
+            let events = staking_events(&stake_res, &staking_account_id);
+
+            let (staked, topics) = events.iter().find_map(|(event, topics)| match event { Event::Staked(staked) => Some((staked, topics)), _ => None }).expect("no Staked event");
+            assert_eq!((staked.pool_id, staked.account, staked.position_id, staked.amount), (DEFAULT_POOL, alice_account, DEFAULT_POSITION, 10000));
+            assert_eq!(topics, &vec![
+                topic(&PrefixedValue { prefix: b"", value: b"StakingContract::Staked" }),
+                topic(&PrefixedValue { prefix: b"StakingContract::Staked::pool_id", value: &DEFAULT_POOL }),
+                topic(&PrefixedValue { prefix: b"StakingContract::Staked::account", value: &alice_account }),
+            ]);
+
+            let set_time = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_timestamp(86400 * 10));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let alice_claim = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.claim_rewards(DEFAULT_POOL));
+            let claim_res = client.call(&ink_e2e::alice(), alice_claim, 0, None).await.expect("claim failed");
+            let events = staking_events(&claim_res, &staking_account_id);
+
+            let (claimed, topics) = events.iter().find_map(|(event, topics)| match event { Event::RewardsClaimed(claimed) => Some((claimed, topics)), _ => None }).expect("no RewardsClaimed event");
+            assert_eq!((claimed.pool_id, claimed.account, claimed.amount), (DEFAULT_POOL, alice_account, 100));
+            assert_eq!(topics, &vec![
+                topic(&PrefixedValue { prefix: b"", value: b"StakingContract::RewardsClaimed" }),
+                topic(&PrefixedValue { prefix: b"StakingContract::RewardsClaimed::pool_id", value: &DEFAULT_POOL }),
+                topic(&PrefixedValue { prefix: b"StakingContract::RewardsClaimed::account", value: &alice_account }),
+            ]);
+
+            // the reserve holds nothing beyond principal, so the rewards were minted
+            let (minted, topics) = events.iter().find_map(|(event, topics)| match event { Event::RewardsMinted(minted) => Some((minted, topics)), _ => None }).expect("no RewardsMinted event");
+            assert_eq!((minted.account, minted.amount), (alice_account, 100));
+            assert_eq!(topics, &vec![
+                topic(&PrefixedValue { prefix: b"", value: b"StakingContract::RewardsMinted" }),
+                topic(&PrefixedValue { prefix: b"StakingContract::RewardsMinted::account", value: &alice_account }),
+            ]);
+
+            let alice_unstake = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.unstake(DEFAULT_POOL, 10000));
+            let unstake_res = client.call(&ink_e2e::alice(), alice_unstake, 0, None).await.expect("unstake failed");
+            let events = staking_events(&unstake_res, &staking_account_id);
+
+            let (unstaked, topics) = events.iter().find_map(|(event, topics)| match event { Event::Unstaked(unstaked) => Some((unstaked, topics)), _ => None }).expect("no Unstaked event");
+            assert_eq!((unstaked.pool_id, unstaked.account, unstaked.amount), (DEFAULT_POOL, alice_account, 10000));
+            assert_eq!(topics, &vec![
+                topic(&PrefixedValue { prefix: b"", value: b"StakingContract::Unstaked" }),
+                topic(&PrefixedValue { prefix: b"StakingContract::Unstaked::pool_id", value: &DEFAULT_POOL }),
+                topic(&PrefixedValue { prefix: b"StakingContract::Unstaked::account", value: &alice_account }),
+            ]);
+
+            let alice_set_rate = build_message::<StakingContractRef>(staking_account_id.clone()).call(|contract| contract.set_reward_rate(DEFAULT_POOL, 2 * REWARD_RATE));
+            let set_rate_res = client.call(&ink_e2e::alice(), alice_set_rate, 0, None).await.expect("set reward rate failed!");
+            let events = staking_events(&set_rate_res, &staking_account_id);
+
+            let (changed, topics) = events.iter().find_map(|(event, topics)| match event { Event::ParametersChanged(changed) => Some((changed, topics)), _ => None }).expect("no ParametersChanged event");
+            assert_eq!(changed.caller, alice_account);
+            assert_eq!(changed.parameter, StakingParameter::RewardRate { pool_id: DEFAULT_POOL, reward_rate: 2 * REWARD_RATE });
+            assert_eq!(topics, &vec![
+                topic(&PrefixedValue { prefix: b"", value: b"StakingContract::ParametersChanged" }),
+                topic(&PrefixedValue { prefix: b"StakingContract::ParametersChanged::caller", value: &alice_account }),
+            ]);
+
+            Ok(())
+        }
     }
 }
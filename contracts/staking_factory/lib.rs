@@ -0,0 +1,336 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[openbrush::implementation(AccessControl)]
+#[openbrush::contract]
+mod staking_factory {
+    use ink::ToAccountId;
+    use openbrush::modifiers;
+    use openbrush::traits::Storage;
+    use staking_app::errors::FactoryError;
+    use staking_app::impls::factory::*;
+    use staking_app::traits::factory::*;
+    use staking_contract::StakingContractRef;
+
+    #[ink(storage)]
+    #[derive(Storage, Default)]
+    pub struct StakingFactoryContract {
+        #[storage_field]
+        factory: FactoryData,
+        #[storage_field]
+        access_control: access_control::Data,
+    }
+
+    #[ink(event)]
+    pub struct PoolDeployed {
+        #[ink(topic)]
+        token: AccountId,
+        #[ink(topic)]
+        pool: AccountId,
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct CodeHashChanged {
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct PoolUpgraded {
+        #[ink(topic)]
+        pool: AccountId,
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct PoolUpgradeSkipped {
+        #[ink(topic)]
+        pool: AccountId,
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        grantee: Option<AccountId>,
+        #[ink(topic)]
+        grantor: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: RoleType,
+        #[ink(topic)]
+        account: Option<AccountId>,
+        #[ink(topic)]
+        sender: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleAdminChanged {
+        #[ink(topic)]
+        role: RoleType,
+        previous_admin_role: RoleType,
+        new_admin_role: RoleType,
+    }
+
+    #[overrider(access_control::Internal)]
+    fn _emit_role_granted(&mut self, role: RoleType, grantee: Option<AccountId>, grantor: Option<AccountId>) {
+        self.env().emit_event(RoleGranted { role, grantee, grantor });
+    }
+
+    #[overrider(access_control::Internal)]
+    fn _emit_role_revoked(&mut self, role: RoleType, account: Option<AccountId>, sender: AccountId) {
+        self.env().emit_event(RoleRevoked { role, account, sender });
+    }
+
+    #[overrider(access_control::Internal)]
+    fn _emit_role_admin_changed(&mut self, role: RoleType, previous_admin_role: RoleType, new_admin_role: RoleType) {
+        self.env().emit_event(RoleAdminChanged { role, previous_admin_role, new_admin_role });
+    }
+
+    impl FactoryImpl for StakingFactoryContract {
+        fn _instantiate_pool(
+            &mut self,
+            code_hash: Hash,
+            token: AccountId,
+            reward_token: AccountId,
+            reward_rate: Balance,
+            salt: &[u8],
+        ) -> Result<AccountId, FactoryError> {
+            let pool = StakingContractRef::new(token, reward_token, reward_rate)
+                .code_hash(code_hash)
+                .endowment(0)
+                .salt_bytes(salt)
+                .try_instantiate()
+                .map_err(|_| FactoryError::InstantiationFailed)?
+                .map_err(|_| FactoryError::InstantiationFailed)?;
+            Ok(pool.to_account_id())
+        }
+
+        fn _emit_pool_deployed_event(&self, token: AccountId, pool: AccountId, code_hash: Hash) {
+            self.env().emit_event(PoolDeployed { token, pool, code_hash });
+        }
+
+        fn _emit_code_hash_changed_event(&self, code_hash: Hash) {
+            self.env().emit_event(CodeHashChanged { code_hash });
+        }
+
+        fn _emit_pool_upgraded_event(&self, pool: AccountId, code_hash: Hash) {
+            self.env().emit_event(PoolUpgraded { pool, code_hash });
+        }
+
+        fn _emit_pool_upgrade_skipped_event(&self, pool: AccountId, code_hash: Hash) {
+            self.env().emit_event(PoolUpgradeSkipped { pool, code_hash });
+        }
+    }
+
+    impl StakingFactory for StakingFactoryContract {
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn create_pool(&mut self, token: AccountId, reward_token: AccountId, reward_rate: Balance) -> Result<AccountId, FactoryError> {
+            self.create_pool_impl(token, reward_token, reward_rate)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn set_code_hash(&mut self, code_hash: Hash) -> Result<(), FactoryError> {
+            self.set_code_hash_impl(code_hash)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        fn upgrade_pools(&mut self, start: u32, limit: u32) -> Result<u32, FactoryError> {
+            self.upgrade_pools_impl(start, limit)
+        }
+
+        #[ink(message)]
+        fn pool_of(&self, token: AccountId) -> Option<AccountId> {
+            self.pool_of_impl(token)
+        }
+
+        #[ink(message)]
+        fn pools(&self) -> Vec<AccountId> {
+            self.pools_impl()
+        }
+
+        #[ink(message)]
+        fn code_hash(&self) -> Option<Hash> {
+            self.code_hash_impl()
+        }
+
+        #[ink(message)]
+        fn pool_code_hash(&self, pool: AccountId) -> Option<Hash> {
+            self.pool_code_hash_impl(pool)
+        }
+    }
+
+    impl StakingFactoryContract {
+        /// `code_hash` must point to uploaded `staking_contract` code.
+        #[ink(constructor)]
+        pub fn new(code_hash: Hash) -> Self {
+            let mut contract = Self::default();
+            contract.factory.code_hash.set(&code_hash);
+
+            let caller = contract.env().caller();
+            access_control::Internal::_init_with_admin(&mut contract, Some(caller));
+            contract
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::build_message;
+        use psp22_token::StakingTokenRef;
+        use staking_legacy::StakingLegacyRef;
+        use staking_app::impls::staking::DEFAULT_POOL;
+        use staking_app::traits::factory::stakingfactory_external::StakingFactory;
+        use staking_app::traits::staking::staking_external::Staking;
+        use staking_app::traits::staking::stakingadmin_external::StakingAdmin;
+        use staking_app::traits::staking::PARAMETER_MANAGER;
+        use staking_app::traits::upgradeable::UPGRADER;
+        use staking_app::traits::upgradeable::upgradeable_external::Upgradeable;
+        use openbrush::contracts::access_control::accesscontrol_external::AccessControl;
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        const REWARD_RATE: Balance = 1000000; // 0.1%
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml ../staking/Cargo.toml")]
+        async fn create_pool_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_code_hash = client.upload("staking_contract", &ink_e2e::alice(), None).await.expect("upload failed").code_hash;
+
+            let factory = StakingFactoryContractRef::new(staking_code_hash);
+
+            let factory_account_id = client.instantiate("staking_factory", &ink_e2e::alice(), factory, 0, None).await.expect("instantiate failed").account_id;
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+
+            let bob_create_pool = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.create_pool(psp22_account_id, psp22_account_id, REWARD_RATE));
+            assert!(client.call(&ink_e2e::bob(), bob_create_pool, 0, None).await.is_err());
+
+            let create_pool = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.create_pool(psp22_account_id, psp22_account_id, REWARD_RATE));
+            let pool_account_id = client.call(&ink_e2e::alice(), create_pool, 0, None).await.expect("create pool failed!").return_value().expect("create pool failed!");
+
+            let create_again = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.create_pool(psp22_account_id, psp22_account_id, REWARD_RATE));
+            assert!(client.call(&ink_e2e::alice(), create_again, 0, None).await.is_err());
+
+            let pool_of = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.pool_of(psp22_account_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pool_of, 0, None).await.return_value(), Some(pool_account_id));
+
+            let pool_code_hash = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.pool_code_hash(pool_account_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pool_code_hash, 0, None).await.return_value(), Some(staking_code_hash));
+
+            let staking_token = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.staking_token(DEFAULT_POOL));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &staking_token, 0, None).await.return_value(), Some(psp22_account_id));
+
+            let has_role = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.has_role(PARAMETER_MANAGER, Some(alice_account)));
+            assert!(client.call_dry_run(&ink_e2e::alice(), &has_role, 0, None).await.return_value());
+
+            let has_role = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.has_role(DEFAULT_ADMIN_ROLE, Some(alice_account)));
+            assert!(client.call_dry_run(&ink_e2e::alice(), &has_role, 0, None).await.return_value());
+
+            // the factory keeps only the upgrade right
+            let has_role = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.has_role(DEFAULT_ADMIN_ROLE, Some(factory_account_id)));
+            assert!(!client.call_dry_run(&ink_e2e::alice(), &has_role, 0, None).await.return_value());
+
+            let has_role = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.has_role(UPGRADER, Some(factory_account_id)));
+            assert!(client.call_dry_run(&ink_e2e::alice(), &has_role, 0, None).await.return_value());
+
+            // the pool's admin runs it without going through the factory
+            let set_time = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.set_timestamp(86400));
+            client.call(&ink_e2e::alice(), set_time, 0, None).await.expect("set timestamp failed!");
+
+            let set_treasury = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.set_treasury(Some(alice_account)));
+            client.call(&ink_e2e::alice(), set_treasury, 0, None).await.expect("set treasury failed!");
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml ../staking/Cargo.toml")]
+        async fn upgrade_pools_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_code_hash = client.upload("staking_contract", &ink_e2e::alice(), None).await.expect("upload failed").code_hash;
+
+            let factory = StakingFactoryContractRef::new(staking_code_hash);
+
+            let factory_account_id = client.instantiate("staking_factory", &ink_e2e::alice(), factory, 0, None).await.expect("instantiate failed").account_id;
+
+            let create_pool = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.create_pool(psp22_account_id, psp22_account_id, REWARD_RATE));
+            let pool_account_id = client.call(&ink_e2e::alice(), create_pool, 0, None).await.expect("create pool failed!").return_value().expect("create pool failed!");
+
+            // nothing to do while every pool runs the stored code hash
+            let upgrade_pools = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.upgrade_pools(0, 10));
+            assert_eq!(client.call(&ink_e2e::alice(), upgrade_pools, 0, None).await.expect("upgrade failed!").return_value(), Ok(0));
+
+            // the pool's admin starts without the upgrade right, though as admin of every role
+            // it may grant the right to itself or revoke the factory's
+            let alice_set_code_hash = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.set_code_hash(staking_code_hash));
+            assert!(client.call(&ink_e2e::alice(), alice_set_code_hash, 0, None).await.is_err());
+
+            let bob_upgrade_pools = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.upgrade_pools(0, 10));
+            assert!(client.call(&ink_e2e::bob(), bob_upgrade_pools, 0, None).await.is_err());
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml ../staking/Cargo.toml ../staking_legacy/Cargo.toml")]
+        async fn upgrade_pools_skips_refusing_pools(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let staking_token = StakingTokenRef::new(1000000);
+            let other_token = StakingTokenRef::new(1000000);
+
+            let psp22_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), staking_token, 0, None).await.expect("instantiate failed").account_id;
+            let other_account_id = client.instantiate("psp22_token", &ink_e2e::alice(), other_token, 0, None).await.expect("instantiate failed").account_id;
+
+            let staking_code_hash = client.upload("staking_contract", &ink_e2e::alice(), None).await.expect("upload failed").code_hash;
+
+            // any other uploaded code serves as the new version
+            let new_code_hash = client.upload("staking_legacy", &ink_e2e::alice(), None).await.expect("upload failed").code_hash;
+
+            let factory = StakingFactoryContractRef::new(staking_code_hash);
+
+            let factory_account_id = client.instantiate("staking_factory", &ink_e2e::alice(), factory, 0, None).await.expect("instantiate failed").account_id;
+
+            let create_pool = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.create_pool(psp22_account_id, psp22_account_id, REWARD_RATE));
+            let pool_account_id = client.call(&ink_e2e::alice(), create_pool, 0, None).await.expect("create pool failed!").return_value().expect("create pool failed!");
+
+            let create_pool = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.create_pool(other_account_id, other_account_id, REWARD_RATE));
+            let other_pool_account_id = client.call(&ink_e2e::alice(), create_pool, 0, None).await.expect("create pool failed!").return_value().expect("create pool failed!");
+
+            // the first pool's admin opts out of factory upgrades
+            let revoke_role = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.revoke_role(UPGRADER, Some(factory_account_id)));
+            client.call(&ink_e2e::alice(), revoke_role, 0, None).await.expect("revoke role failed!");
+
+            let set_code_hash = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.set_code_hash(new_code_hash));
+            client.call(&ink_e2e::alice(), set_code_hash, 0, None).await.expect("set code hash failed!");
+
+            let upgrade_pools = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.upgrade_pools(0, 10));
+            assert_eq!(client.call(&ink_e2e::alice(), upgrade_pools, 0, None).await.expect("upgrade failed!").return_value(), Ok(1));
+
+            let pool_code_hash = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.pool_code_hash(pool_account_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pool_code_hash, 0, None).await.return_value(), Some(staking_code_hash));
+
+            let pool_code_hash = build_message::<StakingFactoryContractRef>(factory_account_id.clone()).call(|contract| contract.pool_code_hash(other_pool_account_id));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &pool_code_hash, 0, None).await.return_value(), Some(new_code_hash));
+
+            // the skipped pool still runs the staking code, the other one answers with the new code
+            let staking_token = build_message::<StakingContractRef>(pool_account_id.clone()).call(|contract| contract.staking_token(DEFAULT_POOL));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &staking_token, 0, None).await.return_value(), Some(psp22_account_id));
+
+            let alice_account = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let get_stake = build_message::<StakingLegacyRef>(other_pool_account_id.clone()).call(|contract| contract.get_stake(alice_account));
+            assert_eq!(client.call_dry_run(&ink_e2e::alice(), &get_stake, 0, None).await.return_value(), None);
+
+            Ok(())
+        }
+    }
+}
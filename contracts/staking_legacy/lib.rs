@@ -0,0 +1,96 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::staking_legacy::StakingLegacyRef;
+
+/// Storage layout of the first staking release, which kept a single stake per account and
+/// had no pools. Only used to test upgrading such a deployment to `staking_contract`, so on
+/// top of staking it has the admin role and `set_code_hash` the first release lacked.
+#[openbrush::implementation(AccessControl)]
+#[openbrush::contract]
+mod staking_legacy {
+    use ink::prelude::vec::Vec;
+    use openbrush::contracts::psp22::PSP22Error;
+    use openbrush::contracts::psp22::PSP22Ref;
+    use openbrush::modifiers;
+    use openbrush::storage::Mapping;
+    use openbrush::traits::Storage;
+
+    /// Storage keys derive from the struct and field names, which therefore match the
+    /// first release's.
+    #[derive(Debug, Default)]
+    #[openbrush::storage_item]
+    pub struct StakingData {
+        pub stakes: Mapping<AccountId, StakeInfo>,
+        #[lazy]
+        pub token: AccountId,
+    }
+
+    #[derive(scale::Decode, scale::Encode, Default, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct StakeInfo {
+        pub amount: Balance,
+        pub timestamp: Timestamp,
+    }
+
+    #[ink(storage)]
+    #[derive(Storage, Default)]
+    pub struct StakingLegacy {
+        #[storage_field]
+        staking: StakingData,
+        #[storage_field]
+        access_control: access_control::Data,
+        pub timestamp: Timestamp
+    }
+
+    impl StakingLegacy {
+        #[ink(constructor)]
+        pub fn new(token: AccountId) -> Self {
+            let mut contract = Self::default();
+            contract.staking.token.set(&token);
+
+            let caller = contract.env().caller();
+            access_control::Internal::_init_with_admin(&mut contract, Some(caller));
+            contract
+        }
+
+        /// Writes a stake record the way the first release did. Staking again replaces
+        /// the record, which is enough for tests staking once per account.
+        #[ink(message)]
+        pub fn stake(&mut self, amount: Balance) -> Result<(), PSP22Error> {
+            let caller = self.env().caller();
+            let token = self.staking.token.get().unwrap_or_default();
+            let stake_info = StakeInfo {
+                amount,
+                timestamp: self.timestamp,
+            };
+            self.staking.stakes.insert(&caller, &stake_info);
+
+            let contract = self.env().account_id();
+            PSP22Ref::transfer_from(&token, caller, contract, amount, Vec::default())
+        }
+
+        #[ink(message)]
+        pub fn get_stake(&self, account: AccountId) -> Option<StakeInfo> {
+            self.staking.stakes.get(&account)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_code_hash(&mut self, code_hash: Hash) -> Result<(), AccessControlError> {
+            self.env()
+                .set_code_hash(&code_hash)
+                .unwrap_or_else(|err| panic!("failed to set code hash: {:?}", err));
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_timestamp(&mut self, timestamp: Timestamp) -> Result<(), AccessControlError> {
+            self.timestamp = timestamp;
+            Ok(())
+        }
+    }
+}